@@ -0,0 +1,49 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{
+    amount::format_cents,
+    db::{AccountType, Db},
+    output::Output,
+};
+
+#[derive(Serialize)]
+struct AccountStatus {
+    name: String,
+    balance: i64,
+    #[serde(rename = "type")]
+    account_type: AccountType,
+}
+
+pub fn print_accounts(db: &mut Db, output: Output) -> Result<()> {
+    let accounts: Vec<AccountStatus> = db
+        .select_account_balances(None)?
+        .into_iter()
+        .map(|(account, balance)| AccountStatus {
+            name: account.name,
+            balance,
+            account_type: account.account_type,
+        })
+        .collect();
+    output.print(&accounts, |accounts| {
+        let width = accounts
+            .iter()
+            .map(|account| account.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        for account in accounts {
+            let account_type = match account.account_type {
+                AccountType::Cash => "",
+                AccountType::Credit => "credit",
+                AccountType::Tracking => "tracking",
+            };
+            let line = format!(
+                "{:width$}  {:>10}  {}",
+                account.name,
+                format_cents(account.balance),
+                account_type,
+            );
+            println!("{}", line.trim_end());
+        }
+    })
+}
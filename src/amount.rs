@@ -0,0 +1,69 @@
+//! Amounts of money are stored as integer cents.
+//! These functions convert between cents and the decimal strings users type.
+
+use anyhow::{anyhow, Result};
+
+/// Parse a decimal string such as `12`, `-3.5`, or `1234.56` into cents.
+pub fn parse_cents(str: &str) -> Result<i64> {
+    let invalid = || anyhow!("invalid amount '{str}': expected a number with at most 2 decimals");
+    let (negative, unsigned) = match str.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, str.strip_prefix('+').unwrap_or(str)),
+    };
+    let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > 2
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let whole: i64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().map_err(|_| invalid())?
+    };
+    let fraction: i64 = format!("{fraction:0<2}").parse().map_err(|_| invalid())?;
+    let cents = whole
+        .checked_mul(100)
+        .and_then(|cents| cents.checked_add(fraction))
+        .ok_or_else(invalid)?;
+    Ok(if negative { -cents } else { cents })
+}
+
+/// Format cents as a decimal string with exactly 2 decimals, e.g. `-3.50`.
+pub fn format_cents(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    format!("{sign}{}.{:02}", cents / 100, cents % 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn parses_partial_decimals() {
+        assert_eq!(parse_cents("3.5").unwrap(), 350);
+        assert_eq!(parse_cents("-.05").unwrap(), -5);
+        assert_eq!(parse_cents("+12").unwrap(), 1200);
+    }
+
+    #[test]
+    fn rejects_malformed_amounts() {
+        for str in ["", "-", ".", "1.234", "1,000", "12a", "--1"] {
+            assert!(parse_cents(str).is_err(), "{str} should not parse");
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn cents_same_after_roundtrip(cents in -(i64::MAX / 100)..(i64::MAX / 100)) {
+            prop_assert_eq!(cents, parse_cents(&format_cents(cents)).unwrap());
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Works out how much to assign to every job at once.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{
+    amount::format_cents,
+    date::YearMonth,
+    db::{Db, Goal, GoalKind},
+    goals,
+    output::Output,
+    table::{Align, Table},
+};
+
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum Strategy {
+    /// Fund each job's goal for the month
+    #[default]
+    Goals,
+    /// Assign what was assigned last month
+    LastMonth,
+    /// Top each job up to its average spending over the last three months
+    AverageSpent,
+}
+
+/// How many past months the average-spent strategy looks at.
+const AVERAGE_MONTHS: i64 = 3;
+
+#[derive(Serialize)]
+struct Plan {
+    month: YearMonth,
+    dry_run: bool,
+    assignments: Vec<Assignment>,
+    /// Cents left in Ready to Assign afterwards
+    ready_to_assign: i64,
+}
+
+#[derive(Serialize)]
+struct Assignment {
+    job: String,
+    amount: i64,
+}
+
+/// Assign to every job according to a strategy, in job order,
+/// until Ready to Assign runs out. Amounts already assigned
+/// this month count toward what a job gets.
+pub fn auto_assign(
+    db: &mut Db,
+    month: YearMonth,
+    strategy: Strategy,
+    dry_run: bool,
+    output: Output,
+) -> Result<()> {
+    let mut ready_to_assign = db.select_ready_to_assign(month)?;
+    let mut assignments = Vec::new();
+    for (job, wanted) in select_wanted(db, month, strategy)? {
+        let amount = wanted.min(ready_to_assign);
+        if amount > 0 {
+            ready_to_assign -= amount;
+            assignments.push(Assignment { job, amount });
+        }
+    }
+    if !dry_run {
+        let amounts: Vec<(String, i64)> = assignments
+            .iter()
+            .map(|assignment| (assignment.job.clone(), assignment.amount))
+            .collect();
+        db.assign_all(month, &amounts)?;
+    }
+    let plan = Plan {
+        month,
+        dry_run,
+        assignments,
+        ready_to_assign,
+    };
+    output.print(&plan, print_text)
+}
+
+/// Pair every job with the cents it should get this month, before Ready to Assign runs out.
+fn select_wanted(db: &mut Db, month: YearMonth, strategy: Strategy) -> Result<Vec<(String, i64)>> {
+    let balances = db.select_month_balances(month)?;
+    let wanted = match strategy {
+        Strategy::Goals => {
+            let goals = db.select_goals()?;
+            balances
+                .into_iter()
+                .filter_map(|(job, balance)| {
+                    let goal = goals.iter().find(|goal| goal.job == job.name)?;
+                    Some((job.name, goals::progress(goal, &balance).underfunded))
+                })
+                .collect()
+        }
+        Strategy::LastMonth => {
+            let last_month = db.select_month_balances(YearMonth::from_int(month.to_int() - 1))?;
+            balances
+                .into_iter()
+                .zip(last_month)
+                .map(|((job, balance), (_, last_balance))| {
+                    (job.name, last_balance.assigned - balance.assigned)
+                })
+                .collect()
+        }
+        Strategy::AverageSpent => {
+            let mut spent = vec![0; balances.len()];
+            for months_ago in 1..=AVERAGE_MONTHS {
+                let past = YearMonth::from_int(month.to_int() - months_ago);
+                for (spent, (_, balance)) in spent.iter_mut().zip(db.select_month_balances(past)?) {
+                    *spent -= balance.activity;
+                }
+            }
+            balances
+                .into_iter()
+                .zip(spent)
+                .map(|(balance, spent)| (balance, spent / AVERAGE_MONTHS))
+                .filter(|(_, average)| *average > 0)
+                .map(|((job, balance), average)| {
+                    // Average spending works like a spending goal
+                    let goal = Goal {
+                        job: job.name.clone(),
+                        kind: GoalKind::Spending,
+                        amount: average,
+                        by: None,
+                    };
+                    (job.name, goals::progress(&goal, &balance).underfunded)
+                })
+                .collect()
+        }
+    };
+    Ok(wanted)
+}
+
+fn print_text(plan: &Plan) {
+    if plan.assignments.is_empty() {
+        println!("Nothing to assign");
+        return;
+    }
+    let mut table = Table::new(&[Align::Left, Align::Right]);
+    for assignment in &plan.assignments {
+        table.push(vec![
+            assignment.job.clone(),
+            format_cents(assignment.amount),
+        ]);
+    }
+    table.print();
+    let total: i64 = plan
+        .assignments
+        .iter()
+        .map(|assignment| assignment.amount)
+        .sum();
+    let verb = if plan.dry_run {
+        "Would assign"
+    } else {
+        "Assigned"
+    };
+    println!(
+        "{verb} {} in {}, leaving {} ready to assign",
+        format_cents(total),
+        plan.month,
+        format_cents(plan.ready_to_assign)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::db::{AccountType, ClearedStatus, NewTransaction};
+
+    #[test]
+    fn goals_are_funded_in_order_until_money_runs_out() -> Result<()> {
+        let mut db = Db::create_in_memory()?;
+        db.insert_account("checking".to_owned(), AccountType::Cash)?;
+        let june = YearMonth {
+            year: 2022,
+            month: 5,
+        };
+        for job in ["rent", "groceries", "fun"] {
+            db.insert_job(job.to_owned())?;
+            db.set_goal(&Goal {
+                job: job.to_owned(),
+                kind: GoalKind::Monthly,
+                amount: 1_000,
+                by: None,
+            })?;
+        }
+        db.insert_transaction(&NewTransaction {
+            date: NaiveDate::from_ymd_opt(2022, 6, 1).unwrap(),
+            account: "checking".to_owned(),
+            amount: 1_500,
+            splits: Vec::new(),
+            payee: String::new(),
+            memo: String::new(),
+            status: ClearedStatus::Uncleared,
+        })?;
+
+        auto_assign(&mut db, june, Strategy::Goals, true, Output::Json)?;
+        assert_eq!(db.select_ready_to_assign(june)?, 1_500);
+
+        auto_assign(&mut db, june, Strategy::Goals, false, Output::Json)?;
+        let assigned: Vec<i64> = db
+            .select_month_balances(june)?
+            .into_iter()
+            .map(|(_, balance)| balance.assigned)
+            .collect();
+        assert_eq!(assigned, vec![1_000, 500, 0]);
+        assert_eq!(db.select_ready_to_assign(june)?, 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+//! Draws bar charts and sparklines out of Unicode block characters.
+
+use colored::{Color, Colorize};
+
+/// How many characters the longest bar in a chart takes up.
+pub const BAR_WIDTH: usize = 20;
+
+/// Blocks filled from the left, from one eighth to the full width of a character.
+const EIGHTHS: [char; 8] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+
+/// Blocks filled from the bottom, from one eighth to the full height of a character.
+const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// A bar as long as `value` is compared to `max`, which gets the full `BAR_WIDTH`.
+/// Empty if the value isn't positive.
+pub fn bar(value: i64, max: i64, color: Color) -> String {
+    let blocks = blocks(value, max, BAR_WIDTH);
+    if blocks.is_empty() {
+        blocks
+    } else {
+        blocks.color(color).to_string()
+    }
+}
+
+/// A green bar for a positive value, or a red one as long as a negative value's size.
+/// `max` is the largest size of any value in the chart.
+pub fn signed_bar(value: i64, max: i64) -> String {
+    if value < 0 {
+        bar(-value, max, Color::Red)
+    } else {
+        bar(value, max, Color::Green)
+    }
+}
+
+/// One character per value, as high as the value is compared to the largest one.
+/// Values that aren't positive are drawn at the lowest level.
+pub fn sparkline(values: &[i64], color: Color) -> String {
+    let max = values.iter().copied().max().unwrap_or(0);
+    let line: String = values
+        .iter()
+        .map(|&value| {
+            let level = if max > 0 {
+                (value.max(0) * (LEVELS.len() as i64 - 1) + max / 2) / max
+            } else {
+                0
+            };
+            LEVELS[level as usize]
+        })
+        .collect();
+    line.color(color).to_string()
+}
+
+fn blocks(value: i64, max: i64, width: usize) -> String {
+    if value <= 0 || max <= 0 {
+        return String::new();
+    }
+    // Anything positive gets at least an eighth, so it doesn't look like nothing
+    let eighths = (value.min(max) as i128 * width as i128 * 8 / max as i128).max(1) as usize;
+    let mut blocks = EIGHTHS[7].to_string().repeat(eighths / 8);
+    match eighths % 8 {
+        0 => {}
+        rest => blocks.push(EIGHTHS[rest - 1]),
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bars_are_drawn_in_eighths() {
+        assert_eq!(blocks(100, 100, 4), "████");
+        assert_eq!(blocks(30, 100, 4), "█▏");
+        assert_eq!(blocks(1, 1_000, 4), "▏");
+        assert_eq!(blocks(-5, 100, 4), "");
+    }
+}
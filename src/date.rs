@@ -1,7 +1,8 @@
-use std::env::VarError;
+use std::{env::VarError, fmt, str::FromStr};
 
 use anyhow::{anyhow, Result};
 use chrono::prelude::*;
+use serde::{Serialize, Serializer};
 
 /// Get the current local date, or get the date from $TBB_DEFAULT_DATE if it exsits.
 pub fn init_date() -> Result<NaiveDate> {
@@ -21,7 +22,7 @@ pub fn format_month_year(date: &NaiveDate) -> String {
 /// YearMonth exists so that the year and month can be combined
 /// into one column in the database. We do this to simplify
 /// comparisons between dates.
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 pub struct YearMonth {
     pub year: i64,
     /// 0-indexed
@@ -34,13 +35,59 @@ impl YearMonth {
     }
 
     pub fn from_int(int: i64) -> YearMonth {
-        let month = ((int % 12) + 12) % 12;
+        let month = int.rem_euclid(12);
         let year = (int - month) / 12;
         YearMonth {
             year,
             month: month as u8,
         }
     }
+
+    pub fn from_date(date: NaiveDate) -> YearMonth {
+        YearMonth {
+            year: date.year() as i64,
+            month: date.month0() as u8,
+        }
+    }
+
+    /// The first day of the month.
+    pub fn first_day(self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.year as i32, self.month as u32 + 1, 1)
+            .expect("year month out of range")
+    }
+
+    /// The last day of the month.
+    pub fn last_day(self) -> NaiveDate {
+        YearMonth::from_int(self.to_int() + 1)
+            .first_day()
+            .pred_opt()
+            .expect("year month out of range")
+    }
+}
+
+/// Formats as yyyy-MM, with a 1-indexed month.
+impl fmt::Display for YearMonth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month + 1)
+    }
+}
+
+/// Serializes the same way as Display.
+impl Serialize for YearMonth {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Parses yyyy-MM, with a 1-indexed month.
+impl FromStr for YearMonth {
+    type Err = anyhow::Error;
+
+    fn from_str(str: &str) -> Result<YearMonth> {
+        NaiveDate::parse_from_str(&format!("{str}-01"), "%Y-%m-%d")
+            .map(YearMonth::from_date)
+            .map_err(|_| anyhow!("invalid month '{str}': expected yyyy-MM"))
+    }
 }
 
 #[cfg(test)]
@@ -55,6 +102,23 @@ mod tests {
         assert_eq!(origin.to_int(), 0);
     }
 
+    #[test]
+    fn year_month_display_roundtrip() {
+        let year_month: YearMonth = "2022-06".parse().unwrap();
+        assert_eq!(
+            year_month,
+            YearMonth {
+                year: 2022,
+                month: 5
+            }
+        );
+        assert_eq!(year_month.to_string(), "2022-06");
+        assert_eq!(
+            year_month.last_day(),
+            NaiveDate::from_ymd_opt(2022, 6, 30).unwrap()
+        );
+    }
+
     proptest! {
         #[test]
         fn year_month_same_after_roundtrip(int: i64) {
@@ -1,11 +1,29 @@
 mod account;
 mod common;
+mod goal;
 mod job;
 mod job_balance;
+mod payee;
+mod report;
+mod rule;
+mod schedule;
+mod split;
+mod template;
+mod transaction;
 
 use anyhow::Result;
 use rusqlite::Connection;
 
+pub use account::AccountType;
+pub use goal::{Goal, GoalKind};
+pub use job::OverspendingPolicy;
+pub use job_balance::JobBalance;
+pub use report::Spending;
+pub use schedule::{Repeat, Schedule};
+pub use transaction::{
+    ClearedStatus, NewTransaction, NewTransfer, TransactionEdit, TransactionFilter,
+};
+
 pub struct Db {
     state: DbState,
 }
@@ -48,6 +66,13 @@ fn init_db(conn: Connection) -> Result<Connection> {
     account::init(&conn)?;
     job::init(&conn)?;
     job_balance::init(&conn)?;
+    payee::init(&conn)?;
+    rule::init(&conn)?;
+    transaction::init(&conn)?;
+    split::init(&conn)?;
+    goal::init(&conn)?;
+    template::init(&conn)?;
+    schedule::init(&conn)?;
     Ok(conn)
 }
 
@@ -1,23 +1,77 @@
 use anyhow::Result;
-use rusqlite::{named_params, Connection};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use rusqlite::{
+    named_params,
+    types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef},
+    Connection, ToSql,
+};
+use serde::Serialize;
 
-use super::common::{
-    rank::{pre_insert, select_max_rank},
-    verify_unique,
+use super::{
+    common::{
+        rank::{pre_insert, select_max_rank},
+        verify_unique,
+    },
+    job,
 };
 
 pub struct Account {
     pub name: String,
     pub rank: i64,
+    pub account_type: AccountType,
     id: i64,
 }
 
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountType {
+    /// Checking, savings, or a wallet
+    #[default]
+    Cash,
+    /// A credit card. Budgeted spending on the card moves money into its payment job
+    Credit,
+    /// An off-budget account, such as investments or a mortgage.
+    /// Its transactions never affect jobs, but it still counts toward net worth
+    Tracking,
+}
+
+impl AccountType {
+    fn as_str(self) -> &'static str {
+        match self {
+            AccountType::Cash => "cash",
+            AccountType::Credit => "credit",
+            AccountType::Tracking => "tracking",
+        }
+    }
+}
+
+impl ToSql for AccountType {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for AccountType {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "cash" => Ok(AccountType::Cash),
+            "credit" => Ok(AccountType::Credit),
+            "tracking" => Ok(AccountType::Tracking),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
 pub fn init(conn: &Connection) -> Result<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS accounts (
             account_id INTEGER NOT NULL PRIMARY KEY,
             name       TEXT NOT NULL COLLATE NOCASE UNIQUE,
-            rank       INTEGER NOT NULL
+            rank       INTEGER NOT NULL,
+            type       TEXT NOT NULL CHECK (type IN ('cash', 'credit', 'tracking')),
+            -- The job that saves up for paying off a credit account
+            payment_job_id INTEGER REFERENCES jobs
         )",
         [],
     )?;
@@ -25,25 +79,96 @@ pub fn init(conn: &Connection) -> Result<()> {
 }
 
 impl super::Db {
-    pub fn insert_account(&mut self, name: String) -> Result<()> {
+    /// Add an account. Credit accounts also get a payment job named after them.
+    pub fn insert_account(&mut self, name: String, account_type: AccountType) -> Result<()> {
         let conn = self.get_conn()?.transaction()?;
         let new_rank = select_max_rank(&conn, "accounts")? + 1;
-        insert(&conn, name, new_rank)?;
+        insert(&conn, name, new_rank, account_type)?;
         conn.commit()?;
         Ok(())
     }
+
+    /// Select every account with the sum of its transactions up to a date, inclusive.
+    pub fn select_account_balances(
+        &mut self,
+        to: Option<NaiveDate>,
+    ) -> Result<Vec<(Account, i64)>> {
+        let conn = self.get_conn()?.transaction()?;
+        let balances = select_balances(&conn, to)?;
+        conn.commit()?;
+        Ok(balances)
+    }
 }
 
-fn insert(conn: &Connection, name: String, rank: i64) -> Result<Account> {
+fn insert(
+    conn: &Connection,
+    name: String,
+    rank: i64,
+    account_type: AccountType,
+) -> Result<Account> {
     verify_unique(conn, "name", name.clone(), "accounts")?;
+    let payment_job_id = match account_type {
+        AccountType::Cash | AccountType::Tracking => None,
+        AccountType::Credit => {
+            let job_rank = select_max_rank(conn, "jobs")? + 1;
+            Some(job::insert(conn, format!("{name} Payment"), job_rank)?.id)
+        }
+    };
     pre_insert(conn, rank, "accounts")?;
     conn.execute(
-        "INSERT INTO accounts (name, rank) VALUES (:name, :rank)",
+        "INSERT INTO accounts (name, rank, type, payment_job_id)
+        VALUES (:name, :rank, :type, :payment_job_id)",
         named_params! {
             ":name": name,
             ":rank": rank,
+            ":type": account_type,
+            ":payment_job_id": payment_job_id,
         },
     )?;
     let id = conn.last_insert_rowid();
-    Ok(Account { id, name, rank })
+    Ok(Account {
+        id,
+        name,
+        rank,
+        account_type,
+    })
+}
+
+pub fn select_type(conn: &Connection, account_id: i64) -> rusqlite::Result<AccountType> {
+    conn.query_row(
+        "SELECT type FROM accounts WHERE account_id == :1",
+        [account_id],
+        |row| row.get(0),
+    )
+}
+
+/// The payment job of a credit account, or None for other accounts.
+pub fn select_payment_job_id(conn: &Connection, account_id: i64) -> rusqlite::Result<Option<i64>> {
+    conn.query_row(
+        "SELECT payment_job_id FROM accounts WHERE account_id == :1",
+        [account_id],
+        |row| row.get(0),
+    )
+}
+
+fn select_balances(conn: &Connection, to: Option<NaiveDate>) -> Result<Vec<(Account, i64)>> {
+    conn.prepare(
+        "SELECT accounts.account_id, name, rank, type, COALESCE(SUM(amount), 0) FROM accounts
+        LEFT JOIN transactions
+            ON transactions.account_id == accounts.account_id
+            AND (:to IS NULL OR date <= :to)
+        GROUP BY accounts.account_id
+        ORDER BY rank ASC",
+    )?
+    .query(named_params! { ":to": to })?
+    .and_then(|row| {
+        let account = Account {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            rank: row.get(2)?,
+            account_type: row.get(3)?,
+        };
+        Ok((account, row.get(4)?))
+    })
+    .collect()
 }
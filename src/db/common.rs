@@ -1,4 +1,4 @@
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
 
 pub mod rank {
     use super::*;
@@ -38,3 +38,19 @@ pub fn verify_unique(
         _ => Err(anyhow::anyhow!("{key} is not unique in {table}")),
     }
 }
+
+/// Look up a row's id by its case-insensitive name.
+pub fn select_id_by_name(
+    conn: &Connection,
+    id_column: &str,
+    name: &str,
+    table: &str,
+) -> anyhow::Result<i64> {
+    conn.query_row(
+        &format!("SELECT {id_column} FROM {table} WHERE name == :1"),
+        [name],
+        |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(|| anyhow::anyhow!("name '{name}' not found in {table}"))
+}
@@ -0,0 +1,132 @@
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use rusqlite::{
+    named_params,
+    types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef},
+    Connection, ToSql,
+};
+use serde::Serialize;
+
+use crate::date::YearMonth;
+
+use super::job::select_outflow_job_id;
+
+/// What a job is saving up for. Each job has at most one goal.
+pub struct Goal {
+    pub job: String,
+    pub kind: GoalKind,
+    /// Cents
+    pub amount: i64,
+    /// The month a target balance should be reached by. Only used by target goals.
+    pub by: Option<YearMonth>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum GoalKind {
+    /// Reach a balance, optionally by a month
+    Target,
+    /// Assign the same amount every month
+    Monthly,
+    /// Have the amount available to spend every month
+    Spending,
+}
+
+impl GoalKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            GoalKind::Target => "target",
+            GoalKind::Monthly => "monthly",
+            GoalKind::Spending => "spending",
+        }
+    }
+}
+
+impl ToSql for GoalKind {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for GoalKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "target" => Ok(GoalKind::Target),
+            "monthly" => Ok(GoalKind::Monthly),
+            "spending" => Ok(GoalKind::Spending),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+pub fn init(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS goals (
+            job_id     INTEGER NOT NULL PRIMARY KEY REFERENCES jobs,
+            kind       TEXT NOT NULL CHECK (kind IN ('target', 'monthly', 'spending')),
+            amount     INTEGER NOT NULL,
+            year_month INTEGER
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+impl super::Db {
+    /// Give a job a goal, replacing any goal it already has.
+    pub fn set_goal(&mut self, goal: &Goal) -> Result<()> {
+        if goal.amount <= 0 {
+            return Err(anyhow!("goal amount must be positive"));
+        }
+        if goal.by.is_some() && goal.kind != GoalKind::Target {
+            return Err(anyhow!("only target goals can have a month"));
+        }
+        let conn = self.get_conn()?.transaction()?;
+        let job_id = select_outflow_job_id(&conn, &goal.job)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO goals (job_id, kind, amount, year_month)
+            VALUES (:job_id, :kind, :amount, :year_month)",
+            named_params! {
+                ":job_id": job_id,
+                ":kind": goal.kind,
+                ":amount": goal.amount,
+                ":year_month": goal.by.map(YearMonth::to_int),
+            },
+        )?;
+        conn.commit()?;
+        Ok(())
+    }
+
+    pub fn clear_goal(&mut self, job_name: &str) -> Result<()> {
+        let conn = self.get_conn()?.transaction()?;
+        let job_id = select_outflow_job_id(&conn, job_name)?;
+        if conn.execute("DELETE FROM goals WHERE job_id == :1", [job_id])? == 0 {
+            return Err(anyhow!("job '{job_name}' has no goal"));
+        }
+        conn.commit()?;
+        Ok(())
+    }
+
+    /// Select every goal, in the order of their jobs.
+    pub fn select_goals(&mut self) -> Result<Vec<Goal>> {
+        let conn = self.get_conn()?.transaction()?;
+        let goals = conn
+            .prepare(
+                "SELECT name, kind, amount, year_month FROM goals
+                JOIN jobs USING (job_id)
+                ORDER BY rank ASC",
+            )?
+            .query([])?
+            .and_then(|row| {
+                Ok(Goal {
+                    job: row.get(0)?,
+                    kind: row.get(1)?,
+                    amount: row.get(2)?,
+                    by: row.get::<_, Option<i64>>(3)?.map(YearMonth::from_int),
+                })
+            })
+            .collect::<Result<_>>()?;
+        conn.commit()?;
+        Ok(goals)
+    }
+}
@@ -1,25 +1,78 @@
-use anyhow::Result;
-use rusqlite::{named_params, Connection};
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use rusqlite::{
+    named_params,
+    types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef},
+    Connection, ToSql,
+};
+use serde::Serialize;
 
 use super::common::{
     rank::{pre_insert, select_max_rank},
-    verify_unique,
+    select_id_by_name, verify_unique,
 };
 
 pub struct Job {
     pub name: String,
     pub rank: i64,
-    id: i64,
+    pub overspending: OverspendingPolicy,
+    /// Jobs in the same group are reported together, e.g. "Bills" or "Fun".
+    pub group: Option<String>,
+    /// Whether the job saves up for paying off a credit account.
+    pub payment: bool,
+    pub(super) id: i64,
+}
+
+/// What happens to a job's negative balance at the end of a month.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum OverspendingPolicy {
+    /// The negative balance carries into next month, to be covered by the job itself
+    #[default]
+    Carry,
+    /// The job starts next month at zero, and the overspending
+    /// comes out of next month's Ready to Assign instead
+    Deduct,
+}
+
+impl OverspendingPolicy {
+    fn as_str(self) -> &'static str {
+        match self {
+            OverspendingPolicy::Carry => "carry",
+            OverspendingPolicy::Deduct => "deduct",
+        }
+    }
+}
+
+impl ToSql for OverspendingPolicy {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for OverspendingPolicy {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "carry" => Ok(OverspendingPolicy::Carry),
+            "deduct" => Ok(OverspendingPolicy::Deduct),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
 }
 
 pub const INFLOW_JOB_ID: i64 = 0;
+/// The inflow job is stored with an empty name, but shown to the user with this one.
+pub const INFLOW_JOB_NAME: &str = "Ready to Assign";
 
 pub fn init(conn: &Connection) -> Result<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS jobs (
             job_id INTEGER NOT NULL PRIMARY KEY,
             name   TEXT NOT NULL COLLATE NOCASE UNIQUE,
-            rank   INTEGER NOT NULL
+            rank   INTEGER NOT NULL,
+            overspending TEXT NOT NULL DEFAULT 'carry'
+                CHECK (overspending IN ('carry', 'deduct')),
+            group_name   TEXT
         )",
         [],
     )?;
@@ -40,6 +93,18 @@ impl super::Db {
         Ok(())
     }
 
+    /// Put a job in a group, or take it out of its group if `group` is None.
+    pub fn set_job_group(&mut self, job_name: &str, group: Option<&str>) -> Result<()> {
+        let conn = self.get_conn()?.transaction()?;
+        let job_id = select_outflow_job_id(&conn, job_name)?;
+        conn.execute(
+            "UPDATE jobs SET group_name = :1 WHERE job_id == :2",
+            (group, job_id),
+        )?;
+        conn.commit()?;
+        Ok(())
+    }
+
     pub fn select_outflow_jobs(&mut self) -> Result<Vec<Job>> {
         let conn = self.get_conn()?.transaction()?;
         let jobs = select_outflow_jobs(&conn)?;
@@ -48,7 +113,11 @@ impl super::Db {
     }
 }
 
-fn insert(conn: &Connection, name: String, rank: i64) -> Result<Job> {
+/// Add a job. The inflow job's display name is reserved, in any case.
+pub fn insert(conn: &Connection, name: String, rank: i64) -> Result<Job> {
+    if name.eq_ignore_ascii_case(INFLOW_JOB_NAME) {
+        return Err(anyhow!("'{name}' is reserved for income"));
+    }
     verify_unique(conn, "name", name.clone(), "jobs")?;
     pre_insert(conn, rank, "jobs")?;
     conn.execute(
@@ -59,12 +128,70 @@ fn insert(conn: &Connection, name: String, rank: i64) -> Result<Job> {
         },
     )?;
     let id = conn.last_insert_rowid();
-    Ok(Job { id, name, rank })
+    Ok(Job {
+        id,
+        name,
+        rank,
+        overspending: OverspendingPolicy::default(),
+        group: None,
+        payment: false,
+    })
 }
 
-fn select_outflow_jobs(conn: &Connection) -> Result<Vec<Job>> {
+/// Look up an outflow job by name. The inflow job can't be selected this way.
+pub fn select_outflow_job_id(conn: &Connection, name: &str) -> Result<i64> {
+    match select_job_id(conn, name)? {
+        INFLOW_JOB_ID => Err(anyhow!("name '{name}' not found in jobs")),
+        job_id => Ok(job_id),
+    }
+}
+
+/// Look up any job by name, including the inflow job by its display name.
+/// The display name always means the inflow job, even if a job was added
+/// with that name before it was reserved.
+pub fn select_job_id(conn: &Connection, name: &str) -> Result<i64> {
+    if name.eq_ignore_ascii_case(INFLOW_JOB_NAME) {
+        return Ok(INFLOW_JOB_ID);
+    }
+    match select_id_by_name(conn, "job_id", name, "jobs")? {
+        // The inflow job's stored name is empty, which isn't a name to look up
+        INFLOW_JOB_ID => Err(anyhow!("name '{name}' not found in jobs")),
+        job_id => Ok(job_id),
+    }
+}
+
+/// Select every job, starting with the inflow job.
+pub fn select_all_jobs(conn: &Connection) -> Result<Vec<Job>> {
     conn.prepare(
-        "SELECT job_id, name, rank FROM jobs
+        "SELECT job_id, name, rank, overspending, group_name,
+            EXISTS (SELECT * FROM accounts WHERE payment_job_id == job_id)
+        FROM jobs
+        ORDER BY job_id != :1, rank ASC",
+    )?
+    .query([INFLOW_JOB_ID])?
+    .and_then(|row| {
+        let id = row.get(0)?;
+        Ok(Job {
+            id,
+            name: if id == INFLOW_JOB_ID {
+                INFLOW_JOB_NAME.to_owned()
+            } else {
+                row.get(1)?
+            },
+            rank: row.get(2)?,
+            overspending: row.get(3)?,
+            group: row.get(4)?,
+            payment: row.get(5)?,
+        })
+    })
+    .collect()
+}
+
+pub fn select_outflow_jobs(conn: &Connection) -> Result<Vec<Job>> {
+    conn.prepare(
+        "SELECT job_id, name, rank, overspending, group_name,
+            EXISTS (SELECT * FROM accounts WHERE payment_job_id == job_id)
+        FROM jobs
         WHERE job_id != :1
         ORDER BY rank ASC",
     )?
@@ -74,7 +201,21 @@ fn select_outflow_jobs(conn: &Connection) -> Result<Vec<Job>> {
             id: row.get(0)?,
             name: row.get(1)?,
             rank: row.get(2)?,
+            overspending: row.get(3)?,
+            group: row.get(4)?,
+            payment: row.get(5)?,
         })
     })
     .collect()
 }
+
+pub fn select_overspending_policy(
+    conn: &Connection,
+    job_id: i64,
+) -> rusqlite::Result<OverspendingPolicy> {
+    conn.query_row(
+        "SELECT overspending FROM jobs WHERE job_id == :1",
+        [job_id],
+        |row| row.get(0),
+    )
+}
@@ -3,6 +3,11 @@ use rusqlite::{Connection, OptionalExtension};
 
 use crate::date::YearMonth;
 
+use super::job::{
+    select_all_jobs, select_outflow_job_id, select_outflow_jobs, select_overspending_policy, Job,
+    OverspendingPolicy, INFLOW_JOB_ID,
+};
+
 pub struct JobBalance {
     pub year_month: YearMonth,
     pub job_id: i64,
@@ -26,16 +31,17 @@ impl JobBalance {
         }
     }
 
-    /// When carrying a balance over to a new month, the balance stays the same,
-    /// but the assigned and activity amounts for the month get reset to 0.
-    fn with_year_month(self, year_month: YearMonth) -> JobBalance {
+    /// When carrying a balance over to a new month, the assigned and activity
+    /// amounts for the month get reset to 0. The balance stays the same,
+    /// unless it is negative and the job's overspending comes out of Ready to Assign.
+    fn with_year_month(self, year_month: YearMonth, policy: OverspendingPolicy) -> JobBalance {
         if year_month == self.year_month {
             self
         } else {
             JobBalance {
                 year_month,
                 job_id: self.job_id,
-                balance: self.balance,
+                balance: carry(self.balance, policy),
                 assigned: 0,
                 activity: 0,
             }
@@ -58,18 +64,199 @@ pub fn init(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+impl super::Db {
+    /// Add cents to the amount assigned to a job in a given month.
+    pub fn assign(&mut self, job_name: &str, year_month: YearMonth, amount: i64) -> Result<()> {
+        let conn = self.get_conn()?.transaction()?;
+        let job_id = select_outflow_job_id(&conn, job_name)?;
+        update_balance(&conn, year_month, job_id, amount, 0)?;
+        conn.commit()?;
+        Ok(())
+    }
+
+    /// Add to the amounts assigned to several jobs in a given month, all or nothing.
+    /// Takes pairs of job names and cents.
+    pub fn assign_all(&mut self, year_month: YearMonth, amounts: &[(String, i64)]) -> Result<()> {
+        let conn = self.get_conn()?.transaction()?;
+        for (job_name, amount) in amounts {
+            let job_id = select_outflow_job_id(&conn, job_name)?;
+            update_balance(&conn, year_month, job_id, *amount, 0)?;
+        }
+        conn.commit()?;
+        Ok(())
+    }
+
+    /// Make every job's assigned amount in one month the same as in another month.
+    pub fn copy_budget(&mut self, from: YearMonth, to: YearMonth) -> Result<()> {
+        let conn = self.get_conn()?.transaction()?;
+        for job in select_outflow_jobs(&conn)? {
+            let assigned = select(&conn, from, job.id)?.assigned;
+            set_assigned(&conn, to, job.id, assigned)?;
+        }
+        conn.commit()?;
+        Ok(())
+    }
+
+    /// Cents of income received up to the end of a month that haven't been assigned
+    /// to a job yet. Money assigned in later months is already spoken for,
+    /// so it is taken out as well, along with overspending in earlier months
+    /// by jobs that deduct it.
+    pub fn select_ready_to_assign(&mut self, year_month: YearMonth) -> Result<i64> {
+        let conn = self.get_conn()?.transaction()?;
+        let ready_to_assign = select_ready_to_assign(&conn, year_month)?;
+        conn.commit()?;
+        Ok(ready_to_assign)
+    }
+
+    /// Cents of income received during a month.
+    pub fn select_income(&mut self, year_month: YearMonth) -> Result<i64> {
+        let conn = self.get_conn()?.transaction()?;
+        let income = select(&conn, year_month, INFLOW_JOB_ID)?.activity;
+        conn.commit()?;
+        Ok(income)
+    }
+
+    /// Cents assigned to jobs in months after a month.
+    pub fn select_assigned_in_future(&mut self, year_month: YearMonth) -> Result<i64> {
+        let conn = self.get_conn()?.transaction()?;
+        let assigned = conn.query_row(
+            "SELECT COALESCE(SUM(assigned), 0) FROM job_balances
+            WHERE year_month > :1 AND job_id != :2",
+            [year_month.to_int(), INFLOW_JOB_ID],
+            |row| row.get(0),
+        )?;
+        conn.commit()?;
+        Ok(assigned)
+    }
+
+    /// Change what happens to a job's negative balances, and recalculate its history.
+    pub fn set_overspending_policy(
+        &mut self,
+        job_name: &str,
+        policy: OverspendingPolicy,
+    ) -> Result<()> {
+        let conn = self.get_conn()?.transaction()?;
+        let job_id = select_outflow_job_id(&conn, job_name)?;
+        conn.execute(
+            "UPDATE jobs SET overspending = :1 WHERE job_id == :2",
+            (policy, job_id),
+        )?;
+        let earliest = conn
+            .query_row(
+                "SELECT MIN(year_month) FROM job_balances WHERE job_id == :1",
+                [job_id],
+                |row| row.get::<_, Option<i64>>(0),
+            )?
+            .map(YearMonth::from_int);
+        if let Some(earliest) = earliest {
+            let balance = select(&conn, earliest, job_id)?.balance;
+            carry_forward(&conn, earliest, job_id, balance)?;
+        }
+        conn.commit()?;
+        Ok(())
+    }
+
+    /// Select the balance of every job for every month in the range, inclusive.
+    /// Each balance is paired with its job's name. The inflow job's balance
+    /// is what was ready to assign in that month, as shown by `select_ready_to_assign`.
+    ///
+    /// If `from` is not given, the range starts at the earliest month with any balance.
+    /// If no balances exist at all, the range starts at `to`.
+    pub fn select_budget(
+        &mut self,
+        from: Option<YearMonth>,
+        to: YearMonth,
+    ) -> Result<Vec<(String, JobBalance)>> {
+        let conn = self.get_conn()?.transaction()?;
+        let from = match from {
+            Some(from) => from,
+            None => select_earliest_year_month(&conn)?.unwrap_or(to),
+        };
+        let jobs = select_all_jobs(&conn)?;
+        let mut budget = Vec::new();
+        for year_month in (from.to_int()..=to.to_int()).map(YearMonth::from_int) {
+            for job in &jobs {
+                let mut balance = select(&conn, year_month, job.id)?;
+                if job.id == INFLOW_JOB_ID {
+                    balance.balance = select_ready_to_assign(&conn, year_month)?;
+                }
+                budget.push((job.name.clone(), balance));
+            }
+        }
+        conn.commit()?;
+        Ok(budget)
+    }
+
+    /// Select the balance of every outflow job for one month.
+    pub fn select_month_balances(
+        &mut self,
+        year_month: YearMonth,
+    ) -> Result<Vec<(Job, JobBalance)>> {
+        let conn = self.get_conn()?.transaction()?;
+        let balances = select_outflow_jobs(&conn)?
+            .into_iter()
+            .map(|job| {
+                let balance = select(&conn, year_month, job.id)?;
+                Ok((job, balance))
+            })
+            .collect::<Result<_>>()?;
+        conn.commit()?;
+        Ok(balances)
+    }
+}
+
+fn select_earliest_year_month(conn: &Connection) -> rusqlite::Result<Option<YearMonth>> {
+    conn.query_row("SELECT MIN(year_month) FROM job_balances", [], |row| {
+        Ok(row.get::<_, Option<i64>>(0)?.map(YearMonth::from_int))
+    })
+}
+
 /// Select a month's running balance.
 ///
 /// If the month has never had any transactions for this job,
 /// this will return a JobBalance with the desired month and
 /// will pull the correct balance from a past month. If there
 /// is no past data, the balance will be 0.
-fn select(conn: &Connection, year_month: YearMonth, job_id: i64) -> Result<JobBalance> {
+pub fn select(conn: &Connection, year_month: YearMonth, job_id: i64) -> Result<JobBalance> {
+    let policy = select_overspending_policy(conn, job_id)?;
     Ok(select_closest(conn, year_month, job_id)?
-        .map(|balance| balance.with_year_month(year_month))
+        .map(|balance| balance.with_year_month(year_month, policy))
         .unwrap_or(JobBalance::new(year_month, job_id)))
 }
 
+/// See `Db::select_ready_to_assign`.
+fn select_ready_to_assign(conn: &Connection, year_month: YearMonth) -> Result<i64> {
+    let inflow = select(conn, year_month, INFLOW_JOB_ID)?.balance;
+    let assigned: i64 = conn.query_row(
+        "SELECT COALESCE(SUM(assigned), 0) FROM job_balances WHERE job_id != :1",
+        [INFLOW_JOB_ID],
+        |row| row.get(0),
+    )?;
+    let deducted = select_deducted_overspending(conn, year_month)?;
+    Ok(inflow - assigned - deducted)
+}
+
+/// The balance a job starts the next month with.
+fn carry(balance: i64, policy: OverspendingPolicy) -> i64 {
+    match policy {
+        OverspendingPolicy::Deduct => balance.max(0),
+        OverspendingPolicy::Carry => balance,
+    }
+}
+
+/// Cents of overspending taken out of Ready to Assign before a month.
+/// A job that deducts its overspending does so at the end of each month
+/// that ends with a negative balance.
+fn select_deducted_overspending(conn: &Connection, year_month: YearMonth) -> rusqlite::Result<i64> {
+    conn.query_row(
+        "SELECT COALESCE(-SUM(balance), 0) FROM job_balances
+        JOIN jobs USING (job_id)
+        WHERE year_month < :1 AND balance < 0 AND overspending == 'deduct'",
+        [year_month.to_int()],
+        |row| row.get(0),
+    )
+}
+
 /// Select the row that has the correct running balance for the desired month.
 ///
 /// If the desired month has no row in the table, this will try to select the closest
@@ -99,7 +286,20 @@ fn select_closest(
     .optional()
 }
 
-fn update_balance(
+/// Change a job's assigned amount in one month to an exact amount, rather than adding to it.
+pub fn set_assigned(
+    conn: &Connection,
+    year_month: YearMonth,
+    job_id: i64,
+    assigned: i64,
+) -> Result<()> {
+    let old_assigned = select(conn, year_month, job_id)?.assigned;
+    update_balance(conn, year_month, job_id, assigned - old_assigned, 0)
+}
+
+/// Add to a job's assigned and activity amounts in one month,
+/// and carry the change over to the running balance of later months.
+pub fn update_balance(
     conn: &Connection,
     year_month: YearMonth,
     job_id: i64,
@@ -119,12 +319,144 @@ fn update_balance(
             job_balance.activity + activity_delta,
         ],
     )?;
-    // Update future months
-    conn.execute(
-        "UPDATE job_balances SET
-            balance = balance + :3 + :4
-        WHERE year_month > :1 AND job_id == :2",
-        [year_month.to_int(), job_id, assigned_delta, activity_delta],
-    )?;
+    carry_forward(
+        conn,
+        year_month,
+        job_id,
+        job_balance.balance + assigned_delta + activity_delta,
+    )
+}
+
+/// Recalculate the running balances of every month after `year_month`,
+/// given the balance at the end of `year_month`.
+fn carry_forward(
+    conn: &Connection,
+    year_month: YearMonth,
+    job_id: i64,
+    balance: i64,
+) -> Result<()> {
+    let policy = select_overspending_policy(conn, job_id)?;
+    let later_months = conn
+        .prepare(
+            "SELECT year_month, assigned, activity FROM job_balances
+            WHERE year_month > :1 AND job_id == :2
+            ORDER BY year_month ASC",
+        )?
+        .query([year_month.to_int(), job_id])?
+        .mapped(|row| Ok((row.get(0)?, row.get::<_, i64>(1)? + row.get::<_, i64>(2)?)))
+        .collect::<rusqlite::Result<Vec<(i64, i64)>>>()?;
+    let mut balance = balance;
+    for (later_month, change) in later_months {
+        balance = carry(balance, policy) + change;
+        conn.execute(
+            "UPDATE job_balances SET balance = :1 WHERE year_month == :2 AND job_id == :3",
+            [balance, later_month, job_id],
+        )?;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Db;
+
+    #[test]
+    fn past_update_carries_into_future_months() -> Result<()> {
+        let mut db = Db::create_in_memory()?;
+        db.insert_job("foo".to_owned())?;
+        let conn = db.get_conn()?;
+        let job_id = select_outflow_job_id(conn, "foo")?;
+        let june = YearMonth {
+            year: 2022,
+            month: 5,
+        };
+        let may = YearMonth {
+            year: 2022,
+            month: 4,
+        };
+        update_balance(conn, june, job_id, 0, -500)?;
+        update_balance(conn, may, job_id, 2000, 0)?;
+        let balance = select(conn, june, job_id)?;
+        assert_eq!(balance.balance, 1500);
+        assert_eq!(balance.assigned, 0);
+        assert_eq!(balance.activity, -500);
+        Ok(())
+    }
+
+    #[test]
+    fn copy_replaces_assigned_amounts() -> Result<()> {
+        let mut db = Db::create_in_memory()?;
+        db.insert_job("foo".to_owned())?;
+        let june = YearMonth {
+            year: 2022,
+            month: 5,
+        };
+        let may = YearMonth {
+            year: 2022,
+            month: 4,
+        };
+        db.assign("foo", may, 300)?;
+        db.assign("foo", june, 500)?;
+        db.copy_budget(may, june)?;
+        let (_, balance) = db.select_month_balances(june)?.remove(0);
+        assert_eq!(balance.assigned, 300);
+        assert_eq!(balance.balance, 600);
+        Ok(())
+    }
+
+    #[test]
+    fn future_assignments_are_not_ready_to_assign() -> Result<()> {
+        let mut db = Db::create_in_memory()?;
+        db.insert_job("foo".to_owned())?;
+        let june = YearMonth {
+            year: 2022,
+            month: 5,
+        };
+        let july = YearMonth {
+            year: 2022,
+            month: 6,
+        };
+        update_balance(db.get_conn()?, june, INFLOW_JOB_ID, 0, 1000)?;
+        db.assign("foo", june, 300)?;
+        db.assign("foo", july, 200)?;
+        assert_eq!(db.select_ready_to_assign(june)?, 500);
+        assert_eq!(db.select_assigned_in_future(june)?, 200);
+        assert_eq!(db.select_ready_to_assign(july)?, 500);
+        assert_eq!(db.select_assigned_in_future(july)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn deducted_overspending_comes_out_of_ready_to_assign() -> Result<()> {
+        let mut db = Db::create_in_memory()?;
+        db.insert_job("foo".to_owned())?;
+        let may = YearMonth {
+            year: 2022,
+            month: 4,
+        };
+        let june = YearMonth {
+            year: 2022,
+            month: 5,
+        };
+        let july = YearMonth {
+            year: 2022,
+            month: 6,
+        };
+        let conn = db.get_conn()?;
+        let job_id = select_outflow_job_id(conn, "foo")?;
+        update_balance(conn, may, INFLOW_JOB_ID, 0, 1000)?;
+        update_balance(conn, may, job_id, 200, -500)?;
+        update_balance(conn, june, job_id, 0, -100)?;
+        assert_eq!(select(conn, june, job_id)?.balance, -400);
+        assert_eq!(db.select_ready_to_assign(june)?, 800);
+
+        db.set_overspending_policy("foo", OverspendingPolicy::Deduct)?;
+        let conn = db.get_conn()?;
+        assert_eq!(select(conn, june, job_id)?.balance, -100);
+        assert_eq!(select(conn, july, job_id)?.balance, 0);
+        assert_eq!(db.select_ready_to_assign(june)?, 500);
+        assert_eq!(db.select_ready_to_assign(july)?, 400);
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use anyhow::{anyhow, Result};
+use rusqlite::{named_params, Connection, OptionalExtension};
+
+use super::job::INFLOW_JOB_ID;
+
+pub struct Payee {
+    pub name: String,
+    /// The job most recently chosen for this payee.
+    /// Used when a transaction is recorded without a job.
+    pub job: Option<String>,
+    /// Other names that resolve to this payee, such as raw bank descriptions.
+    pub aliases: Vec<String>,
+    id: i64,
+}
+
+pub fn init(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS payees (
+            payee_id INTEGER NOT NULL PRIMARY KEY,
+            name     TEXT NOT NULL COLLATE NOCASE UNIQUE,
+            job_id   INTEGER REFERENCES jobs
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS payee_aliases (
+            alias    TEXT NOT NULL COLLATE NOCASE PRIMARY KEY,
+            payee_id INTEGER NOT NULL REFERENCES payees
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+impl super::Db {
+    /// Select every payee in alphabetical order.
+    pub fn select_payees(&mut self) -> Result<Vec<Payee>> {
+        let conn = self.get_conn()?.transaction()?;
+        let mut payees = conn
+            .prepare(
+                "SELECT payee_id, payees.name, jobs.name FROM payees
+                LEFT JOIN jobs USING (job_id)
+                ORDER BY payees.name ASC",
+            )?
+            .query([])?
+            .and_then(|row| {
+                Ok(Payee {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    job: row.get(2)?,
+                    aliases: Vec::new(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        for payee in &mut payees {
+            payee.aliases = select_aliases(&conn, payee.id)?;
+        }
+        conn.commit()?;
+        Ok(payees)
+    }
+
+    pub fn rename_payee(&mut self, old_name: &str, new_name: String) -> Result<()> {
+        if new_name.is_empty() {
+            return Err(anyhow!("payee name can't be empty"));
+        }
+        let conn = self.get_conn()?.transaction()?;
+        let payee_id = select_id(&conn, old_name)?;
+        match select_id_or_alias(&conn, &new_name)? {
+            Some(id) if id != payee_id => {
+                return Err(anyhow!("name is not unique in payees"));
+            }
+            Some(_) => {
+                // Renaming to one of the payee's own aliases, or changing its case
+                conn.execute("DELETE FROM payee_aliases WHERE alias == :1", [&new_name])?;
+            }
+            None => {}
+        }
+        conn.execute(
+            "UPDATE payees SET name = :1 WHERE payee_id == :2",
+            (&new_name, payee_id),
+        )?;
+        conn.commit()?;
+        Ok(())
+    }
+
+    /// Move every transaction and alias of one payee onto another,
+    /// and keep the merged payee's name as an alias.
+    pub fn merge_payees(&mut self, from_name: &str, into_name: &str) -> Result<()> {
+        let conn = self.get_conn()?.transaction()?;
+        let from_id = select_id(&conn, from_name)?;
+        let into_id = select_id(&conn, into_name)?;
+        if from_id == into_id {
+            return Err(anyhow!("cannot merge a payee into itself"));
+        }
+        let params = named_params! { ":from": from_id, ":into": into_id };
+        conn.execute(
+            "UPDATE transactions SET payee_id = :into WHERE payee_id == :from",
+            params,
+        )?;
+        conn.execute(
+            "UPDATE payee_aliases SET payee_id = :into WHERE payee_id == :from",
+            params,
+        )?;
+        conn.execute(
+            "UPDATE payees SET job_id = (SELECT job_id FROM payees WHERE payee_id == :from)
+            WHERE payee_id == :into AND job_id IS NULL",
+            params,
+        )?;
+        conn.execute(
+            "INSERT INTO payee_aliases (alias, payee_id)
+            SELECT name, :into FROM payees WHERE payee_id == :from",
+            params,
+        )?;
+        conn.execute("DELETE FROM payees WHERE payee_id == :1", [from_id])?;
+        conn.commit()?;
+        Ok(())
+    }
+}
+
+/// Resolve a name or alias to a payee, creating a new payee if there is no match.
+/// Returns None if the name is empty.
+pub fn select_or_insert(conn: &Connection, name: &str) -> Result<Option<i64>> {
+    if name.is_empty() {
+        return Ok(None);
+    }
+    if let Some(id) = select_id_or_alias(conn, name)? {
+        return Ok(Some(id));
+    }
+    conn.execute("INSERT INTO payees (name) VALUES (:1)", [name])?;
+    Ok(Some(conn.last_insert_rowid()))
+}
+
+pub fn select_job_id(conn: &Connection, payee_id: i64) -> rusqlite::Result<Option<i64>> {
+    conn.query_row(
+        "SELECT job_id FROM payees WHERE payee_id == :1",
+        [payee_id],
+        |row| row.get(0),
+    )
+}
+
+pub fn select_name(conn: &Connection, payee_id: i64) -> rusqlite::Result<String> {
+    conn.query_row(
+        "SELECT name FROM payees WHERE payee_id == :1",
+        [payee_id],
+        |row| row.get(0),
+    )
+}
+
+/// Remember the job chosen for a payee. The inflow job is never remembered.
+pub fn update_job_id(conn: &Connection, payee_id: i64, job_id: i64) -> rusqlite::Result<()> {
+    if job_id != INFLOW_JOB_ID {
+        conn.execute(
+            "UPDATE payees SET job_id = :1 WHERE payee_id == :2",
+            [job_id, payee_id],
+        )?;
+    }
+    Ok(())
+}
+
+pub fn select_id(conn: &Connection, name: &str) -> Result<i64> {
+    select_id_or_alias(conn, name)?.ok_or_else(|| anyhow!("name '{name}' not found in payees"))
+}
+
+/// Look up a payee by name, falling back to aliases.
+fn select_id_or_alias(conn: &Connection, name: &str) -> rusqlite::Result<Option<i64>> {
+    conn.query_row(
+        "SELECT payee_id FROM payees WHERE name == :1
+        UNION ALL
+        SELECT payee_id FROM payee_aliases WHERE alias == :1",
+        [name],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+fn select_aliases(conn: &Connection, payee_id: i64) -> rusqlite::Result<Vec<String>> {
+    conn.prepare("SELECT alias FROM payee_aliases WHERE payee_id == :1 ORDER BY alias ASC")?
+        .query([payee_id])?
+        .mapped(|row| row.get(0))
+        .collect()
+}
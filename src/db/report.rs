@@ -0,0 +1,170 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use rusqlite::named_params;
+
+use super::job::INFLOW_JOB_ID;
+
+/// Money that left the budget, as part of one transaction.
+pub struct Spending {
+    pub date: NaiveDate,
+    /// None if the transaction is uncategorized.
+    pub job: Option<String>,
+    pub group: Option<String>,
+    pub payee: Option<String>,
+    /// Cents. Unlike transaction amounts, spending is positive and refunds are negative.
+    pub amount: i64,
+}
+
+impl super::Db {
+    /// Select every split that spent from a job between two dates, inclusive,
+    /// and every uncategorized transaction in a budget account, in date order.
+    ///
+    /// Income isn't spending, and neither is paying off a credit card,
+    /// since the spending was counted when it was charged to the card.
+    /// Transfers between budget accounts are left out for the same reason,
+    /// but a transfer into a tracking account takes money out of the budget, so it counts.
+    pub fn select_spending(&mut self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Spending>> {
+        let conn = self.get_conn()?.transaction()?;
+        let spending = conn
+            .prepare(
+                "SELECT date, jobs.name, jobs.group_name, payees.name, -splits.amount
+                FROM splits
+                JOIN transactions USING (transaction_id)
+                JOIN jobs USING (job_id)
+                LEFT JOIN payees USING (payee_id)
+                WHERE date BETWEEN :from AND :to
+                    AND splits.job_id != :inflow_job_id
+                    AND splits.job_id NOT IN (
+                        SELECT payment_job_id FROM accounts WHERE payment_job_id IS NOT NULL
+                    )
+                UNION ALL
+                SELECT date, NULL, NULL, payees.name, -amount
+                FROM transactions
+                JOIN accounts USING (account_id)
+                LEFT JOIN payees USING (payee_id)
+                WHERE date BETWEEN :from AND :to
+                    AND type != 'tracking'
+                    AND NOT EXISTS (
+                        SELECT * FROM transactions AS other
+                        JOIN accounts AS other_account
+                            ON other_account.account_id == other.account_id
+                        WHERE other.transaction_id == transactions.transfer_id
+                            AND other_account.type != 'tracking'
+                    )
+                    AND NOT EXISTS (
+                        SELECT * FROM splits
+                        WHERE splits.transaction_id == transactions.transaction_id
+                    )
+                ORDER BY date ASC",
+            )?
+            .query(named_params! {
+                ":from": from,
+                ":to": to,
+                ":inflow_job_id": INFLOW_JOB_ID,
+            })?
+            .and_then(|row| {
+                Ok(Spending {
+                    date: row.get(0)?,
+                    job: row.get(1)?,
+                    group: row.get(2)?,
+                    payee: row.get(3)?,
+                    amount: row.get(4)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        conn.commit()?;
+        Ok(spending)
+    }
+
+    /// Select the date and amount of every split that brought money into Ready to Assign,
+    /// up to a date, inclusive, in date order.
+    pub fn select_inflows(&mut self, to: NaiveDate) -> Result<Vec<(NaiveDate, i64)>> {
+        let conn = self.get_conn()?.transaction()?;
+        let inflows = conn
+            .prepare(
+                "SELECT date, splits.amount FROM splits
+                JOIN transactions USING (transaction_id)
+                WHERE job_id == :1 AND splits.amount > 0 AND date <= :2
+                ORDER BY date ASC",
+            )?
+            .query((INFLOW_JOB_ID, to))?
+            .and_then(|row| Ok((row.get(0)?, row.get(1)?)))
+            .collect::<Result<_>>()?;
+        conn.commit()?;
+        Ok(inflows)
+    }
+
+    /// The date of the earliest transaction, if there are any.
+    pub fn select_first_date(&mut self) -> Result<Option<NaiveDate>> {
+        let conn = self.get_conn()?.transaction()?;
+        let date = conn.query_row("SELECT MIN(date) FROM transactions", [], |row| row.get(0))?;
+        conn.commit()?;
+        Ok(date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{AccountType, ClearedStatus, Db, NewTransaction, NewTransfer};
+
+    fn transaction(account: &str, amount: i64, job: Option<&str>) -> NewTransaction {
+        NewTransaction {
+            date: NaiveDate::from_ymd_opt(2022, 6, 1).unwrap(),
+            account: account.to_owned(),
+            amount,
+            splits: job.iter().map(|job| (job.to_string(), amount)).collect(),
+            payee: String::new(),
+            memo: String::new(),
+            status: ClearedStatus::Uncleared,
+        }
+    }
+
+    #[test]
+    fn card_payments_and_income_are_not_spending() -> Result<()> {
+        let mut db = Db::create_in_memory()?;
+        db.insert_account("checking".to_owned(), AccountType::Cash)?;
+        db.insert_account("visa".to_owned(), AccountType::Credit)?;
+        db.insert_account("house".to_owned(), AccountType::Tracking)?;
+        db.insert_job("groceries".to_owned())?;
+        db.insert_transaction(&transaction("checking", 1_000, None))?;
+        db.insert_transaction(&transaction("visa", -300, Some("groceries")))?;
+        db.insert_transaction(&transaction("checking", -50, None))?;
+        db.insert_transaction(&transaction("house", -5_000, None))?;
+        db.insert_transfer(&NewTransfer {
+            date: NaiveDate::from_ymd_opt(2022, 6, 2).unwrap(),
+            from: "checking".to_owned(),
+            to: "visa".to_owned(),
+            amount: 300,
+            job: None,
+            memo: String::new(),
+        })?;
+        let to_house = NewTransfer {
+            date: NaiveDate::from_ymd_opt(2022, 6, 3).unwrap(),
+            from: "checking".to_owned(),
+            to: "house".to_owned(),
+            amount: 100,
+            job: None,
+            memo: String::new(),
+        };
+        db.insert_transfer(&to_house)?;
+        db.insert_transfer(&NewTransfer {
+            from: "house".to_owned(),
+            to: "checking".to_owned(),
+            amount: 30,
+            ..to_house
+        })?;
+
+        let june = |day| NaiveDate::from_ymd_opt(2022, 6, day).unwrap();
+        let spending = db.select_spending(june(1), june(30))?;
+        let amounts: Vec<(Option<String>, i64)> = spending
+            .into_iter()
+            .map(|spending| (spending.job, spending.amount))
+            .collect();
+        assert_eq!(
+            amounts,
+            vec![(Some("groceries".to_owned()), 300), (None, 50), (None, 100)]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use rusqlite::{named_params, Connection};
+
+use super::common::{rank::select_max_rank, select_id_by_name};
+use super::job::select_outflow_job_id;
+
+/// Categorizes transactions whose payee matches a regex.
+/// Rules are checked in order of rank, and the first match wins.
+pub struct Rule {
+    pub id: i64,
+    pub payee_regex: String,
+    pub job: String,
+    /// If present, the rule only applies to transactions in this account.
+    pub account: Option<String>,
+    pub rank: i64,
+    pub(super) job_id: i64,
+}
+
+pub fn init(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS rules (
+            rule_id     INTEGER NOT NULL PRIMARY KEY,
+            payee_regex TEXT NOT NULL,
+            job_id      INTEGER NOT NULL REFERENCES jobs,
+            account_id  INTEGER REFERENCES accounts,
+            rank        INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+impl super::Db {
+    /// Add a rule with lower priority than every existing rule.
+    pub fn insert_rule(
+        &mut self,
+        payee_regex: String,
+        job_name: &str,
+        account_name: Option<&str>,
+    ) -> Result<()> {
+        Regex::new(&payee_regex).map_err(|err| anyhow!("invalid payee regex: {err}"))?;
+        let conn = self.get_conn()?.transaction()?;
+        let job_id = select_outflow_job_id(&conn, job_name)?;
+        let account_id = account_name
+            .map(|name| select_id_by_name(&conn, "account_id", name, "accounts"))
+            .transpose()?;
+        let rank = select_max_rank(&conn, "rules")? + 1;
+        conn.execute(
+            "INSERT INTO rules (payee_regex, job_id, account_id, rank)
+            VALUES (:payee_regex, :job_id, :account_id, :rank)",
+            named_params! {
+                ":payee_regex": payee_regex,
+                ":job_id": job_id,
+                ":account_id": account_id,
+                ":rank": rank,
+            },
+        )?;
+        conn.commit()?;
+        Ok(())
+    }
+
+    pub fn select_rules(&mut self) -> Result<Vec<Rule>> {
+        let conn = self.get_conn()?.transaction()?;
+        let rules = select_applicable(&conn, None)?;
+        conn.commit()?;
+        Ok(rules)
+    }
+
+    /// Find the rule that would categorize a transaction with this payee in an account.
+    /// Without an account, rules limited to an account don't match.
+    pub fn select_matching_rule(
+        &mut self,
+        payee: &str,
+        account_name: Option<&str>,
+    ) -> Result<Option<Rule>> {
+        let conn = self.get_conn()?.transaction()?;
+        let account_id = account_name
+            .map(|name| select_id_by_name(&conn, "account_id", name, "accounts"))
+            .transpose()?;
+        let rule = select_matching(&conn, payee, account_id)?;
+        conn.commit()?;
+        Ok(rule)
+    }
+}
+
+/// Find the first rule, by rank, whose regex matches the payee.
+///
+/// Rules limited to an account only match when `account_id` is that account.
+pub fn select_matching(
+    conn: &Connection,
+    payee: &str,
+    account_id: Option<i64>,
+) -> Result<Option<Rule>> {
+    let rules = select_applicable(conn, account_id)?
+        .into_iter()
+        .filter(|rule| account_id.is_some() || rule.account.is_none());
+    for rule in rules {
+        if Regex::new(&rule.payee_regex)?.is_match(payee) {
+            return Ok(Some(rule));
+        }
+    }
+    Ok(None)
+}
+
+/// Select rules in order of rank.
+/// If `account_id` is None, every rule is selected.
+/// Otherwise, rules limited to other accounts are left out.
+fn select_applicable(conn: &Connection, account_id: Option<i64>) -> Result<Vec<Rule>> {
+    conn.prepare(
+        "SELECT rule_id, payee_regex, rules.job_id, jobs.name, accounts.name, rules.rank
+        FROM rules
+        JOIN jobs USING (job_id)
+        LEFT JOIN accounts USING (account_id)
+        WHERE :account_id IS NULL
+            OR rules.account_id IS NULL
+            OR rules.account_id == :account_id
+        ORDER BY rules.rank ASC",
+    )?
+    .query(named_params! { ":account_id": account_id })?
+    .and_then(|row| {
+        Ok(Rule {
+            id: row.get(0)?,
+            payee_regex: row.get(1)?,
+            job_id: row.get(2)?,
+            job: row.get(3)?,
+            account: row.get(4)?,
+            rank: row.get(5)?,
+        })
+    })
+    .collect()
+}
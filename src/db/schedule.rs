@@ -0,0 +1,286 @@
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Months, NaiveDate};
+use clap::ValueEnum;
+use rusqlite::{
+    named_params,
+    types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef},
+    Connection, ToSql,
+};
+use serde::Serialize;
+
+use super::{
+    account::{select_type, AccountType},
+    common::select_id_by_name,
+    job::select_outflow_job_id,
+    payee,
+    transaction::{self, ClearedStatus, NewTransaction},
+};
+
+/// A transaction that repeats on a regular schedule, such as rent or a salary.
+pub struct Schedule {
+    pub id: i64,
+    pub account: String,
+    /// Cents. Spending X cents is negative X.
+    pub amount: i64,
+    pub payee: Option<String>,
+    /// If None, occurrences are categorized like any transaction recorded without a job.
+    pub job: Option<String>,
+    pub memo: String,
+    pub repeat: Repeat,
+    /// Repeat every this many weeks, months or years.
+    pub every: u32,
+    /// The date of the first occurrence. Later occurrences keep its day of the month,
+    /// or use the last day of shorter months.
+    pub start: NaiveDate,
+    /// No occurrences after this date, inclusive.
+    pub end: Option<NaiveDate>,
+    /// How many occurrences have been recorded so far.
+    pub recorded: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Repeat {
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Repeat {
+    fn as_str(self) -> &'static str {
+        match self {
+            Repeat::Weekly => "weekly",
+            Repeat::Monthly => "monthly",
+            Repeat::Yearly => "yearly",
+        }
+    }
+}
+
+impl ToSql for Repeat {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for Repeat {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "weekly" => Ok(Repeat::Weekly),
+            "monthly" => Ok(Repeat::Monthly),
+            "yearly" => Ok(Repeat::Yearly),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+impl Schedule {
+    /// The date of an occurrence, counting from 0. None if the schedule has ended by then.
+    pub fn occurrence(&self, index: u32) -> Option<NaiveDate> {
+        let steps = index.checked_mul(self.every)?;
+        let date = match self.repeat {
+            Repeat::Weekly => self
+                .start
+                .checked_add_signed(Duration::weeks(steps as i64))?,
+            Repeat::Monthly => self.start.checked_add_months(Months::new(steps))?,
+            Repeat::Yearly => self
+                .start
+                .checked_add_months(Months::new(steps.checked_mul(12)?))?,
+        };
+        match self.end {
+            Some(end) if date > end => None,
+            _ => Some(date),
+        }
+    }
+
+    /// The date of the next occurrence that hasn't been recorded yet.
+    pub fn next(&self) -> Option<NaiveDate> {
+        self.occurrence(self.recorded)
+    }
+
+    fn new_transaction(&self, date: NaiveDate) -> NewTransaction {
+        NewTransaction {
+            date,
+            account: self.account.clone(),
+            amount: self.amount,
+            splits: self
+                .job
+                .iter()
+                .map(|job| (job.clone(), self.amount))
+                .collect(),
+            payee: self.payee.clone().unwrap_or_default(),
+            memo: self.memo.clone(),
+            status: ClearedStatus::Uncleared,
+        }
+    }
+}
+
+pub fn init(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schedules (
+            schedule_id INTEGER NOT NULL PRIMARY KEY,
+            account_id  INTEGER NOT NULL REFERENCES accounts,
+            amount      INTEGER NOT NULL,
+            payee_id    INTEGER REFERENCES payees,
+            job_id      INTEGER REFERENCES jobs,
+            memo        TEXT NOT NULL,
+            repeat      TEXT NOT NULL CHECK (repeat IN ('weekly', 'monthly', 'yearly')),
+            every       INTEGER NOT NULL CHECK (every > 0),
+            start_date  TEXT NOT NULL,
+            end_date    TEXT,
+            recorded    INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+impl super::Db {
+    /// Add a schedule. Its `id` and `recorded` fields are ignored.
+    pub fn insert_schedule(&mut self, schedule: &Schedule) -> Result<()> {
+        if schedule.every == 0 {
+            return Err(anyhow!("schedules must repeat at least every 1 period"));
+        }
+        let conn = self.get_conn()?.transaction()?;
+        let account_id = select_id_by_name(&conn, "account_id", &schedule.account, "accounts")?;
+        let payee_id = match &schedule.payee {
+            Some(name) => payee::select_or_insert(&conn, name)?,
+            None => None,
+        };
+        let job_id = schedule
+            .job
+            .as_ref()
+            .map(|name| select_outflow_job_id(&conn, name))
+            .transpose()?;
+        if job_id.is_some() && select_type(&conn, account_id)? == AccountType::Tracking {
+            return Err(anyhow!("transactions in tracking accounts can't have jobs"));
+        }
+        conn.execute(
+            "INSERT INTO schedules (account_id, amount, payee_id, job_id, memo,
+                repeat, every, start_date, end_date, recorded)
+            VALUES (:account_id, :amount, :payee_id, :job_id, :memo,
+                :repeat, :every, :start_date, :end_date, 0)",
+            named_params! {
+                ":account_id": account_id,
+                ":amount": schedule.amount,
+                ":payee_id": payee_id,
+                ":job_id": job_id,
+                ":memo": schedule.memo,
+                ":repeat": schedule.repeat,
+                ":every": schedule.every,
+                ":start_date": schedule.start,
+                ":end_date": schedule.end,
+            },
+        )?;
+        conn.commit()?;
+        Ok(())
+    }
+
+    pub fn delete_schedule(&mut self, id: i64) -> Result<()> {
+        let conn = self.get_conn()?.transaction()?;
+        if conn.execute("DELETE FROM schedules WHERE schedule_id == :1", [id])? == 0 {
+            return Err(anyhow!("schedule {id} not found"));
+        }
+        conn.commit()?;
+        Ok(())
+    }
+
+    /// Select every schedule in the order they were added.
+    pub fn select_schedules(&mut self) -> Result<Vec<Schedule>> {
+        let conn = self.get_conn()?.transaction()?;
+        let schedules = select_all(&conn)?;
+        conn.commit()?;
+        Ok(schedules)
+    }
+
+    /// Record every occurrence of every schedule up to a date, inclusive.
+    /// Returns how many transactions were recorded.
+    pub fn run_schedules(&mut self, to: NaiveDate) -> Result<usize> {
+        let conn = self.get_conn()?.transaction()?;
+        let mut count = 0;
+        for mut schedule in select_all(&conn)? {
+            while let Some(date) = schedule.next().filter(|&date| date <= to) {
+                transaction::insert(&conn, &schedule.new_transaction(date))?;
+                schedule.recorded += 1;
+                count += 1;
+            }
+            conn.execute(
+                "UPDATE schedules SET recorded = :1 WHERE schedule_id == :2",
+                (schedule.recorded, schedule.id),
+            )?;
+        }
+        conn.commit()?;
+        Ok(count)
+    }
+}
+
+fn select_all(conn: &Connection) -> Result<Vec<Schedule>> {
+    conn.prepare(
+        "SELECT schedule_id, accounts.name, amount, payees.name, jobs.name, memo,
+            repeat, every, start_date, end_date, recorded
+        FROM schedules
+        JOIN accounts USING (account_id)
+        LEFT JOIN payees USING (payee_id)
+        LEFT JOIN jobs USING (job_id)
+        ORDER BY schedule_id ASC",
+    )?
+    .query([])?
+    .and_then(|row| {
+        Ok(Schedule {
+            id: row.get(0)?,
+            account: row.get(1)?,
+            amount: row.get(2)?,
+            payee: row.get(3)?,
+            job: row.get(4)?,
+            memo: row.get(5)?,
+            repeat: row.get(6)?,
+            every: row.get(7)?,
+            start: row.get(8)?,
+            end: row.get(9)?,
+            recorded: row.get(10)?,
+        })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(repeat: Repeat, every: u32, start: NaiveDate) -> Schedule {
+        Schedule {
+            id: 1,
+            account: "checking".to_owned(),
+            amount: -100,
+            payee: None,
+            job: None,
+            memo: String::new(),
+            repeat,
+            every,
+            start,
+            end: None,
+            recorded: 0,
+        }
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn monthly_keeps_the_day_of_the_month() {
+        let rent = schedule(Repeat::Monthly, 1, date(2022, 1, 31));
+        assert_eq!(rent.occurrence(1), Some(date(2022, 2, 28)));
+        assert_eq!(rent.occurrence(2), Some(date(2022, 3, 31)));
+    }
+
+    #[test]
+    fn weekly_every_two_weeks_stops_at_the_end() {
+        let pay = Schedule {
+            end: Some(date(2022, 6, 29)),
+            ..schedule(Repeat::Weekly, 2, date(2022, 6, 1))
+        };
+        assert_eq!(pay.occurrence(1), Some(date(2022, 6, 15)));
+        assert_eq!(pay.occurrence(2), Some(date(2022, 6, 29)));
+        assert_eq!(pay.occurrence(3), None);
+    }
+}
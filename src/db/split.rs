@@ -0,0 +1,125 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use rusqlite::{named_params, Connection};
+
+use crate::date::YearMonth;
+
+use super::{
+    job::{INFLOW_JOB_ID, INFLOW_JOB_NAME},
+    job_balance::update_balance,
+};
+
+/// The part of a transaction's amount that belongs to one job.
+/// A transaction's splits always add up to its amount,
+/// except for uncategorized transactions, which have no splits.
+pub struct Split {
+    pub job: String,
+    /// Cents. Spending X cents is stored as negative X.
+    pub amount: i64,
+    pub(super) job_id: i64,
+}
+
+pub fn init(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS splits (
+            split_id       INTEGER NOT NULL PRIMARY KEY,
+            transaction_id INTEGER NOT NULL REFERENCES transactions,
+            job_id         INTEGER NOT NULL REFERENCES jobs,
+            amount         INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Add a split to a transaction and update the job's activity for the transaction's month.
+///
+/// `payment_job_id` is the payment job of the transaction's account, if it is a credit account.
+/// Budgeted spending on a credit account moves the same amount into its payment job,
+/// so the money set aside for the spending is now set aside for paying the card.
+pub fn insert(
+    conn: &Connection,
+    transaction_id: i64,
+    date: NaiveDate,
+    job_id: i64,
+    amount: i64,
+    payment_job_id: Option<i64>,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO splits (transaction_id, job_id, amount)
+        VALUES (:transaction_id, :job_id, :amount)",
+        named_params! {
+            ":transaction_id": transaction_id,
+            ":job_id": job_id,
+            ":amount": amount,
+        },
+    )?;
+    update_balance(conn, YearMonth::from_date(date), job_id, 0, amount)?;
+    update_payment_balance(conn, date, job_id, amount, payment_job_id)
+}
+
+/// Remove every split from a transaction and undo their effect on job balances.
+/// `date` and `payment_job_id` must be the ones the splits were inserted with.
+pub fn delete_all(
+    conn: &Connection,
+    transaction_id: i64,
+    date: NaiveDate,
+    payment_job_id: Option<i64>,
+) -> Result<()> {
+    for split in select(conn, transaction_id)? {
+        update_balance(
+            conn,
+            YearMonth::from_date(date),
+            split.job_id,
+            0,
+            -split.amount,
+        )?;
+        update_payment_balance(conn, date, split.job_id, -split.amount, payment_job_id)?;
+    }
+    conn.execute(
+        "DELETE FROM splits WHERE transaction_id == :1",
+        [transaction_id],
+    )?;
+    Ok(())
+}
+
+/// Move a split's amount into or out of a credit account's payment job.
+/// Income and payments to the card itself don't move anything.
+fn update_payment_balance(
+    conn: &Connection,
+    date: NaiveDate,
+    job_id: i64,
+    amount: i64,
+    payment_job_id: Option<i64>,
+) -> Result<()> {
+    match payment_job_id {
+        Some(payment_job_id) if job_id != INFLOW_JOB_ID && job_id != payment_job_id => {
+            update_balance(conn, YearMonth::from_date(date), payment_job_id, 0, -amount)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Select a transaction's splits in the order they were added.
+pub fn select(conn: &Connection, transaction_id: i64) -> Result<Vec<Split>> {
+    conn.prepare(
+        "SELECT job_id, name, amount FROM splits
+        JOIN jobs USING (job_id)
+        WHERE transaction_id == :1
+        ORDER BY split_id ASC",
+    )?
+    .query([transaction_id])?
+    .and_then(|row| {
+        let job_id = row.get(0)?;
+        Ok(Split {
+            job_id,
+            job: if job_id == INFLOW_JOB_ID {
+                INFLOW_JOB_NAME.to_owned()
+            } else {
+                row.get(1)?
+            },
+            amount: row.get(2)?,
+        })
+    })
+    .collect()
+}
@@ -0,0 +1,127 @@
+use anyhow::{anyhow, Result};
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::date::YearMonth;
+
+use super::{
+    job::select_outflow_jobs,
+    job_balance::{select, set_assigned},
+};
+
+/// A saved set of assigned amounts that can be applied to any month.
+pub struct Template {
+    pub name: String,
+    /// Pairs of job names and cents, in job order.
+    pub amounts: Vec<(String, i64)>,
+    id: i64,
+}
+
+pub fn init(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS templates (
+            template_id INTEGER NOT NULL PRIMARY KEY,
+            name        TEXT NOT NULL COLLATE NOCASE UNIQUE
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS template_amounts (
+            template_id INTEGER NOT NULL REFERENCES templates,
+            job_id      INTEGER NOT NULL REFERENCES jobs,
+            amount      INTEGER NOT NULL,
+            PRIMARY KEY (template_id, job_id)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+impl super::Db {
+    /// Save the amounts assigned in a month under a name, replacing any template
+    /// with the same name. Jobs with nothing assigned are left out.
+    pub fn save_template(&mut self, name: &str, year_month: YearMonth) -> Result<()> {
+        let conn = self.get_conn()?.transaction()?;
+        let template_id = match select_id(&conn, name)? {
+            Some(id) => {
+                conn.execute("DELETE FROM template_amounts WHERE template_id == :1", [id])?;
+                id
+            }
+            None => {
+                conn.execute("INSERT INTO templates (name) VALUES (:1)", [name])?;
+                conn.last_insert_rowid()
+            }
+        };
+        for job in select_outflow_jobs(&conn)? {
+            let assigned = select(&conn, year_month, job.id)?.assigned;
+            if assigned != 0 {
+                conn.execute(
+                    "INSERT INTO template_amounts (template_id, job_id, amount)
+                    VALUES (:1, :2, :3)",
+                    [template_id, job.id, assigned],
+                )?;
+            }
+        }
+        conn.commit()?;
+        Ok(())
+    }
+
+    /// Set the assigned amount of every job in a template.
+    /// Jobs that aren't in the template keep their assigned amounts.
+    pub fn apply_template(&mut self, name: &str, year_month: YearMonth) -> Result<()> {
+        let conn = self.get_conn()?.transaction()?;
+        let template_id = select_id(&conn, name)?
+            .ok_or_else(|| anyhow!("name '{name}' not found in templates"))?;
+        for (job_id, amount) in select_amounts(&conn, template_id)? {
+            set_assigned(&conn, year_month, job_id, amount)?;
+        }
+        conn.commit()?;
+        Ok(())
+    }
+
+    /// Select every template in alphabetical order.
+    pub fn select_templates(&mut self) -> Result<Vec<Template>> {
+        let conn = self.get_conn()?.transaction()?;
+        let mut templates = conn
+            .prepare("SELECT template_id, name FROM templates ORDER BY name ASC")?
+            .query([])?
+            .and_then(|row| {
+                Ok(Template {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    amounts: Vec::new(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        for template in &mut templates {
+            template.amounts = conn
+                .prepare(
+                    "SELECT name, amount FROM template_amounts
+                    JOIN jobs USING (job_id)
+                    WHERE template_id == :1
+                    ORDER BY rank ASC",
+                )?
+                .query([template.id])?
+                .mapped(|row| Ok((row.get(0)?, row.get(1)?)))
+                .collect::<rusqlite::Result<_>>()?;
+        }
+        conn.commit()?;
+        Ok(templates)
+    }
+}
+
+fn select_id(conn: &Connection, name: &str) -> rusqlite::Result<Option<i64>> {
+    conn.query_row(
+        "SELECT template_id FROM templates WHERE name == :1",
+        [name],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Pairs of job ids and cents.
+fn select_amounts(conn: &Connection, template_id: i64) -> rusqlite::Result<Vec<(i64, i64)>> {
+    conn.prepare("SELECT job_id, amount FROM template_amounts WHERE template_id == :1")?
+        .query([template_id])?
+        .mapped(|row| Ok((row.get(0)?, row.get(1)?)))
+        .collect()
+}
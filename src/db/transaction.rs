@@ -0,0 +1,873 @@
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use rusqlite::{
+    named_params,
+    types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef},
+    Connection, OptionalExtension, ToSql,
+};
+use serde::Serialize;
+
+use crate::amount::format_cents;
+
+use super::{
+    account::{select_payment_job_id, select_type, AccountType},
+    common::select_id_by_name,
+    job::{select_job_id, select_outflow_job_id, INFLOW_JOB_ID},
+    payee,
+    rule::select_matching,
+    split::{self, Split},
+};
+
+pub struct Transaction {
+    pub id: i64,
+    pub date: NaiveDate,
+    pub account: String,
+    /// Empty if the transaction is uncategorized.
+    pub splits: Vec<Split>,
+    /// Cents. Spending X cents is stored as negative X.
+    pub amount: i64,
+    pub payee: Option<String>,
+    pub memo: String,
+    pub status: ClearedStatus,
+    /// The other account, if this is one side of a transfer.
+    pub transfer: Option<String>,
+}
+
+/// Whether a transaction has shown up on a bank statement.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ClearedStatus {
+    /// Not yet seen on a statement
+    Uncleared,
+    /// Seen on a statement, but not yet reconciled
+    Cleared,
+    /// Part of a reconciliation. Reconciled transactions can't be changed
+    Reconciled,
+}
+
+impl ClearedStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            ClearedStatus::Uncleared => "uncleared",
+            ClearedStatus::Cleared => "cleared",
+            ClearedStatus::Reconciled => "reconciled",
+        }
+    }
+}
+
+impl ToSql for ClearedStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for ClearedStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "uncleared" => Ok(ClearedStatus::Uncleared),
+            "cleared" => Ok(ClearedStatus::Cleared),
+            "reconciled" => Ok(ClearedStatus::Reconciled),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// A transaction that hasn't been recorded yet.
+pub struct NewTransaction {
+    pub date: NaiveDate,
+    pub account: String,
+    /// Cents. Spending X cents is negative X.
+    pub amount: i64,
+    /// Pairs of job names and cents. Must add up to the amount, or be empty.
+    pub splits: Vec<(String, i64)>,
+    /// Name or alias. Empty if there is no payee.
+    pub payee: String,
+    pub memo: String,
+    pub status: ClearedStatus,
+}
+
+/// Money moved from one account to another.
+/// A transfer is recorded as a pair of transactions, one in each account.
+pub struct NewTransfer {
+    pub date: NaiveDate,
+    pub from: String,
+    pub to: String,
+    /// Cents. Must be positive.
+    pub amount: i64,
+    /// The job to spend from when moving money from a budget account to a tracking account.
+    /// Left uncategorized if None.
+    pub job: Option<String>,
+    pub memo: String,
+}
+
+/// Narrows down which transactions get selected. Every field is optional.
+#[derive(Default)]
+pub struct TransactionFilter {
+    pub account: Option<String>,
+    /// Matches transactions with at least one split in this job.
+    pub job: Option<String>,
+    /// Matches by payee name or alias.
+    pub payee: Option<String>,
+    /// Inclusive
+    pub from: Option<NaiveDate>,
+    /// Inclusive
+    pub to: Option<NaiveDate>,
+    /// Inclusive, in cents
+    pub min: Option<i64>,
+    /// Inclusive, in cents
+    pub max: Option<i64>,
+    /// Case-insensitive substring of the memo
+    pub memo: Option<String>,
+    /// Only matches transactions without splits.
+    pub uncategorized: bool,
+    pub status: Option<ClearedStatus>,
+}
+
+/// Changes to a recorded transaction. Fields that are None keep their old value.
+///
+/// If the job is left alone, a transaction with one split keeps its job.
+/// A split transaction keeps its splits, unless the amount changes, in which
+/// case new splits are required. Moving a transaction into a tracking account
+/// removes its splits.
+#[derive(Default)]
+pub struct TransactionEdit {
+    pub date: Option<NaiveDate>,
+    pub account: Option<String>,
+    /// Cents
+    pub amount: Option<i64>,
+    /// Replaces the splits with one split for the whole amount. May be the inflow job.
+    pub job: Option<String>,
+    /// If not empty, replaces the splits. Must add up to the amount.
+    pub splits: Vec<(String, i64)>,
+    /// Removes every split.
+    pub uncategorized: bool,
+    pub payee: Option<String>,
+    pub memo: Option<String>,
+}
+
+pub fn init(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS transactions (
+            transaction_id INTEGER NOT NULL PRIMARY KEY,
+            date           TEXT NOT NULL,
+            account_id     INTEGER NOT NULL REFERENCES accounts,
+            amount         INTEGER NOT NULL,
+            payee_id       INTEGER REFERENCES payees,
+            memo           TEXT NOT NULL,
+            status         TEXT NOT NULL
+                CHECK (status IN ('uncleared', 'cleared', 'reconciled')),
+            -- The other side of a transfer
+            transfer_id    INTEGER REFERENCES transactions
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+impl super::Db {
+    /// Record a transaction and update its jobs' activity.
+    ///
+    /// The payee is resolved by name or alias, and created if it doesn't exist yet.
+    ///
+    /// Without splits, the first rule matching the payee picks the job.
+    /// If no rule matches, the payee's most recently chosen job is used.
+    /// Failing that, positive amounts go to the inflow job
+    /// and negative amounts are left uncategorized.
+    ///
+    /// Transactions in tracking accounts never have splits.
+    pub fn insert_transaction(&mut self, transaction: &NewTransaction) -> Result<i64> {
+        let conn = self.get_conn()?.transaction()?;
+        let id = insert(&conn, transaction)?;
+        conn.commit()?;
+        Ok(id)
+    }
+
+    /// Record a transfer as a pair of linked transactions.
+    ///
+    /// Transfers between budget accounts don't change how much money there is to budget,
+    /// so neither side has splits. The exception is paying off a credit account
+    /// from a cash account, which spends from the credit account's payment job.
+    ///
+    /// Money moved into a tracking account leaves the budget, so it is spent from
+    /// the transfer's job. Money moved out of a tracking account is income.
+    pub fn insert_transfer(&mut self, transfer: &NewTransfer) -> Result<()> {
+        if transfer.amount <= 0 {
+            return Err(anyhow!("transfer amount must be positive"));
+        }
+        let conn = self.get_conn()?.transaction()?;
+        let from_id = select_id_by_name(&conn, "account_id", &transfer.from, "accounts")?;
+        let to_id = select_id_by_name(&conn, "account_id", &transfer.to, "accounts")?;
+        if from_id == to_id {
+            return Err(anyhow!("cannot transfer from an account to itself"));
+        }
+        let insert_side = |account_id, amount| {
+            conn.execute(
+                "INSERT INTO transactions (date, account_id, amount, memo, status)
+                VALUES (:date, :account_id, :amount, :memo, 'uncleared')",
+                named_params! {
+                    ":date": transfer.date,
+                    ":account_id": account_id,
+                    ":amount": amount,
+                    ":memo": transfer.memo,
+                },
+            )
+            .map(|_| conn.last_insert_rowid())
+        };
+        let from_tx_id = insert_side(from_id, -transfer.amount)?;
+        let to_tx_id = insert_side(to_id, transfer.amount)?;
+        conn.execute(
+            "UPDATE transactions SET transfer_id = ?2 WHERE transaction_id == ?1",
+            [from_tx_id, to_tx_id],
+        )?;
+        conn.execute(
+            "UPDATE transactions SET transfer_id = ?2 WHERE transaction_id == ?1",
+            [to_tx_id, from_tx_id],
+        )?;
+        let from_payment_job_id = select_payment_job_id(&conn, from_id)?;
+        let to_payment_job_id = select_payment_job_id(&conn, to_id)?;
+        let (from_type, to_type) = (select_type(&conn, from_id)?, select_type(&conn, to_id)?);
+        let job_id = match &transfer.job {
+            Some(_) if from_type == AccountType::Tracking || to_type != AccountType::Tracking => {
+                return Err(anyhow!(
+                    "only transfers from a budget account to a tracking account have a job"
+                ))
+            }
+            Some(job_name) => Some(select_outflow_job_id(&conn, job_name)?),
+            None => None,
+        };
+        let split = match (from_type, to_type) {
+            (AccountType::Cash, AccountType::Credit) => to_payment_job_id
+                .map(|job_id| (from_tx_id, job_id, -transfer.amount, from_payment_job_id)),
+            (AccountType::Tracking, AccountType::Cash | AccountType::Credit) => {
+                Some((to_tx_id, INFLOW_JOB_ID, transfer.amount, to_payment_job_id))
+            }
+            (AccountType::Cash | AccountType::Credit, AccountType::Tracking) => {
+                job_id.map(|job_id| (from_tx_id, job_id, -transfer.amount, from_payment_job_id))
+            }
+            _ => None,
+        };
+        if let Some((tx_id, job_id, amount, payment_job_id)) = split {
+            split::insert(&conn, tx_id, transfer.date, job_id, amount, payment_job_id)?;
+        }
+        conn.commit()?;
+        Ok(())
+    }
+
+    /// Change a recorded transaction. The old splits are undone and the new ones applied,
+    /// so job balances stay correct even when the transaction moves to another month.
+    ///
+    /// Income and uncategorized transactions whose amount changes sign
+    /// get their job picked again, as if they were recorded anew.
+    pub fn update_transaction(&mut self, id: i64, edit: &TransactionEdit) -> Result<()> {
+        let conn = self.get_conn()?.transaction()?;
+        let old = select_unlocked_row(&conn, id)?;
+        if old.transfer_id.is_some() {
+            return Err(anyhow!(
+                "transaction {id} is a transfer; delete it and record it again instead"
+            ));
+        }
+        let old_splits = split::select(&conn, id)?;
+        let date = edit.date.unwrap_or(old.date);
+        let amount = edit.amount.unwrap_or(old.amount);
+        let account_id = match &edit.account {
+            Some(name) => select_id_by_name(&conn, "account_id", name, "accounts")?,
+            None => old.account_id,
+        };
+        let payee_id = match &edit.payee {
+            Some(name) => payee::select_or_insert(&conn, name)?,
+            None => old.payee_id,
+        };
+        let memo = edit.memo.as_ref().unwrap_or(&old.memo);
+        let off_budget = is_off_budget(&conn, account_id)?;
+        if off_budget && (edit.job.is_some() || !edit.splits.is_empty()) {
+            return Err(anyhow!("transactions in tracking accounts can't have jobs"));
+        }
+        // Splits that weren't chosen, but derived from the sign of the amount
+        let derived = match old_splits.as_slice() {
+            [] => true,
+            [split] => split.job_id == INFLOW_JOB_ID,
+            _ => false,
+        };
+        let split_ids = if edit.uncategorized || off_budget {
+            Vec::new()
+        } else if let Some(job_name) = &edit.job {
+            let job_id = select_job_id(&conn, job_name)?;
+            if let Some(payee_id) = payee_id {
+                payee::update_job_id(&conn, payee_id, job_id)?;
+            }
+            vec![(job_id, amount)]
+        } else if !edit.splits.is_empty() {
+            verify_splits_sum(&edit.splits, amount)?;
+            select_split_job_ids(&conn, &edit.splits, payee_id)?
+        } else if derived && (amount > 0) != (old.amount > 0) {
+            let payee = match payee_id {
+                Some(payee_id) => payee::select_name(&conn, payee_id)?,
+                None => String::new(),
+            };
+            select_default_job_id(&conn, &payee, payee_id, account_id, amount)?
+                .map(|job_id| (job_id, amount))
+                .into_iter()
+                .collect()
+        } else {
+            match old_splits.as_slice() {
+                [] => Vec::new(),
+                [split] => vec![(split.job_id, amount)],
+                splits if amount == old.amount => splits
+                    .iter()
+                    .map(|split| (split.job_id, split.amount))
+                    .collect(),
+                _ => {
+                    return Err(anyhow!(
+                        "transaction {id} is split, so a new amount needs new splits"
+                    ))
+                }
+            }
+        };
+        split::delete_all(
+            &conn,
+            id,
+            old.date,
+            select_payment_job_id(&conn, old.account_id)?,
+        )?;
+        conn.execute(
+            "UPDATE transactions SET
+                date = :date,
+                account_id = :account_id,
+                amount = :amount,
+                payee_id = :payee_id,
+                memo = :memo
+            WHERE transaction_id == :id",
+            named_params! {
+                ":date": date,
+                ":account_id": account_id,
+                ":amount": amount,
+                ":payee_id": payee_id,
+                ":memo": memo,
+                ":id": id,
+            },
+        )?;
+        let payment_job_id = select_payment_job_id(&conn, account_id)?;
+        for (job_id, amount) in split_ids {
+            split::insert(&conn, id, date, job_id, amount, payment_job_id)?;
+        }
+        conn.commit()?;
+        Ok(())
+    }
+
+    /// Delete a transaction and undo its effect on job balances.
+    /// Deleting one side of a transfer deletes both sides.
+    pub fn delete_transaction(&mut self, id: i64) -> Result<()> {
+        let conn = self.get_conn()?.transaction()?;
+        let old = select_unlocked_row(&conn, id)?;
+        let mut rows = vec![(id, old)];
+        if let Some(transfer_id) = rows[0].1.transfer_id {
+            rows.push((transfer_id, select_unlocked_row(&conn, transfer_id)?));
+            // Unlink the sides first, since each one references the other
+            conn.execute(
+                "UPDATE transactions SET transfer_id = NULL WHERE transaction_id IN (:1, :2)",
+                [id, transfer_id],
+            )?;
+        }
+        for (id, row) in rows {
+            split::delete_all(
+                &conn,
+                id,
+                row.date,
+                select_payment_job_id(&conn, row.account_id)?,
+            )?;
+            conn.execute("DELETE FROM transactions WHERE transaction_id == :1", [id])?;
+        }
+        conn.commit()?;
+        Ok(())
+    }
+
+    /// Mark a transaction as cleared or uncleared.
+    pub fn update_cleared_status(&mut self, id: i64, status: ClearedStatus) -> Result<()> {
+        if status == ClearedStatus::Reconciled {
+            return Err(anyhow!(
+                "transactions can only be reconciled with tbb reconcile"
+            ));
+        }
+        let conn = self.get_conn()?.transaction()?;
+        select_unlocked_row(&conn, id)?;
+        conn.execute(
+            "UPDATE transactions SET status = :1 WHERE transaction_id == :2",
+            (status, id),
+        )?;
+        conn.commit()?;
+        Ok(())
+    }
+
+    /// Sum of an account's cleared and reconciled transactions.
+    pub fn select_cleared_balance(&mut self, account_name: &str) -> Result<i64> {
+        let conn = self.get_conn()?.transaction()?;
+        let account_id = select_id_by_name(&conn, "account_id", account_name, "accounts")?;
+        let balance = conn.query_row(
+            "SELECT COALESCE(SUM(amount), 0) FROM transactions
+            WHERE account_id == :1 AND status != 'uncleared'",
+            [account_id],
+            |row| row.get(0),
+        )?;
+        conn.commit()?;
+        Ok(balance)
+    }
+
+    /// Lock every cleared transaction in an account by marking it reconciled.
+    /// Returns how many transactions were reconciled.
+    pub fn reconcile_cleared(&mut self, account_name: &str) -> Result<usize> {
+        let conn = self.get_conn()?.transaction()?;
+        let account_id = select_id_by_name(&conn, "account_id", account_name, "accounts")?;
+        let count = conn.execute(
+            "UPDATE transactions SET status = 'reconciled'
+            WHERE account_id == :1 AND status == 'cleared'",
+            [account_id],
+        )?;
+        conn.commit()?;
+        Ok(count)
+    }
+
+    /// Select transactions matching a filter, in chronological order.
+    pub fn select_transactions(&mut self, filter: &TransactionFilter) -> Result<Vec<Transaction>> {
+        let conn = self.get_conn()?.transaction()?;
+        let transactions = select_filtered(&conn, filter)?;
+        conn.commit()?;
+        Ok(transactions)
+    }
+}
+
+/// Record a transaction. See `Db::insert_transaction`.
+pub fn insert(conn: &Connection, transaction: &NewTransaction) -> Result<i64> {
+    let NewTransaction {
+        date,
+        amount,
+        ref splits,
+        ref payee,
+        ..
+    } = *transaction;
+    verify_splits_sum(splits, amount)?;
+    let account_id = select_id_by_name(conn, "account_id", &transaction.account, "accounts")?;
+    let payee_id = payee::select_or_insert(conn, payee)?;
+    let off_budget = is_off_budget(conn, account_id)?;
+    if off_budget && !splits.is_empty() {
+        return Err(anyhow!("transactions in tracking accounts can't have jobs"));
+    }
+    let split_ids = match splits.as_slice() {
+        _ if off_budget => Vec::new(),
+        [] => select_default_job_id(conn, payee, payee_id, account_id, amount)?
+            .map(|job_id| (job_id, amount))
+            .into_iter()
+            .collect(),
+        splits => select_split_job_ids(conn, splits, payee_id)?,
+    };
+    conn.execute(
+        "INSERT INTO transactions (date, account_id, amount, payee_id, memo, status)
+        VALUES (:date, :account_id, :amount, :payee_id, :memo, :status)",
+        named_params! {
+            ":date": date,
+            ":account_id": account_id,
+            ":amount": amount,
+            ":payee_id": payee_id,
+            ":memo": transaction.memo,
+            ":status": transaction.status,
+        },
+    )?;
+    let id = conn.last_insert_rowid();
+    let payment_job_id = select_payment_job_id(conn, account_id)?;
+    for (job_id, amount) in split_ids {
+        split::insert(conn, id, date, job_id, amount, payment_job_id)?;
+    }
+    Ok(id)
+}
+
+/// Pick the job of a transaction recorded without splits: the first rule matching the payee,
+/// then the payee's most recently chosen job, then the inflow job for positive amounts.
+/// None means the transaction is left uncategorized.
+fn select_default_job_id(
+    conn: &Connection,
+    payee: &str,
+    payee_id: Option<i64>,
+    account_id: i64,
+    amount: i64,
+) -> Result<Option<i64>> {
+    let rule_job_id = select_matching(conn, payee, Some(account_id))?.map(|rule| rule.job_id);
+    let payee_job_id = match payee_id {
+        Some(payee_id) => payee::select_job_id(conn, payee_id)?,
+        None => None,
+    };
+    Ok(rule_job_id
+        .or(payee_job_id)
+        .or((amount > 0).then_some(INFLOW_JOB_ID)))
+}
+
+/// Resolve the job names of explicitly chosen splits.
+///
+/// If there is only one split, the payee remembers its job,
+/// since the choice is unambiguous.
+fn select_split_job_ids(
+    conn: &Connection,
+    splits: &[(String, i64)],
+    payee_id: Option<i64>,
+) -> Result<Vec<(i64, i64)>> {
+    let split_ids = splits
+        .iter()
+        .map(|(job_name, amount)| Ok((select_outflow_job_id(conn, job_name)?, *amount)))
+        .collect::<Result<Vec<_>>>()?;
+    if let (Some(payee_id), [(job_id, _)]) = (payee_id, split_ids.as_slice()) {
+        payee::update_job_id(conn, payee_id, *job_id)?;
+    }
+    Ok(split_ids)
+}
+
+/// Whether an account is a tracking account, whose transactions never have splits.
+fn is_off_budget(conn: &Connection, account_id: i64) -> Result<bool> {
+    Ok(select_type(conn, account_id)? == AccountType::Tracking)
+}
+
+/// The columns of one transaction, without joining any other tables.
+struct TransactionRow {
+    date: NaiveDate,
+    account_id: i64,
+    amount: i64,
+    payee_id: Option<i64>,
+    memo: String,
+    transfer_id: Option<i64>,
+}
+
+/// Select a transaction that is allowed to change, i.e. one that isn't reconciled.
+fn select_unlocked_row(conn: &Connection, id: i64) -> Result<TransactionRow> {
+    let (row, status) = conn
+        .query_row(
+            "SELECT date, account_id, amount, payee_id, memo, transfer_id, status
+            FROM transactions
+            WHERE transaction_id == :1",
+            [id],
+            |row| {
+                let transaction = TransactionRow {
+                    date: row.get(0)?,
+                    account_id: row.get(1)?,
+                    amount: row.get(2)?,
+                    payee_id: row.get(3)?,
+                    memo: row.get(4)?,
+                    transfer_id: row.get(5)?,
+                };
+                Ok((transaction, row.get(6)?))
+            },
+        )
+        .optional()?
+        .ok_or_else(|| anyhow!("transaction {id} not found"))?;
+    match status {
+        ClearedStatus::Reconciled => Err(anyhow!(
+            "transaction {id} is reconciled and can't be changed"
+        )),
+        _ => Ok(row),
+    }
+}
+
+/// Splits must add up to the transaction's amount. No splits at all is also allowed.
+fn verify_splits_sum(splits: &[(String, i64)], amount: i64) -> Result<()> {
+    let sum: i64 = splits.iter().map(|(_, amount)| amount).sum();
+    if splits.is_empty() || sum == amount {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "splits add up to {} but the transaction amount is {}",
+            format_cents(sum),
+            format_cents(amount)
+        ))
+    }
+}
+
+fn select_filtered(conn: &Connection, filter: &TransactionFilter) -> Result<Vec<Transaction>> {
+    // Resolve names up front so that typos are errors rather than empty results
+    let account_id = filter
+        .account
+        .as_ref()
+        .map(|name| select_id_by_name(conn, "account_id", name, "accounts"))
+        .transpose()?;
+    let job_id = filter
+        .job
+        .as_ref()
+        .map(|name| select_job_id(conn, name))
+        .transpose()?;
+    let payee_id = filter
+        .payee
+        .as_ref()
+        .map(|name| payee::select_id(conn, name))
+        .transpose()?;
+    let mut transactions = conn
+        .prepare(
+            "SELECT transaction_id, date, accounts.name, amount, payees.name, memo, status,
+                (SELECT name FROM transactions AS other
+                    JOIN accounts USING (account_id)
+                    WHERE other.transaction_id == transactions.transfer_id)
+            FROM transactions
+            JOIN accounts USING (account_id)
+            LEFT JOIN payees USING (payee_id)
+            WHERE (:from IS NULL OR date >= :from)
+                AND (:to IS NULL OR date <= :to)
+                AND (:account_id IS NULL OR account_id == :account_id)
+                AND (:payee_id IS NULL OR payee_id == :payee_id)
+                AND (:min IS NULL OR amount >= :min)
+                AND (:max IS NULL OR amount <= :max)
+                AND (:memo IS NULL OR instr(lower(memo), lower(:memo)) > 0)
+                AND (:status IS NULL OR status == :status)
+                AND (:job_id IS NULL OR EXISTS (
+                    SELECT * FROM splits
+                    WHERE splits.transaction_id == transactions.transaction_id
+                        AND splits.job_id == :job_id
+                ))
+                AND (NOT :uncategorized OR NOT EXISTS (
+                    SELECT * FROM splits
+                    WHERE splits.transaction_id == transactions.transaction_id
+                ))
+            ORDER BY date ASC, transaction_id ASC",
+        )?
+        .query(named_params! {
+            ":from": filter.from,
+            ":to": filter.to,
+            ":account_id": account_id,
+            ":payee_id": payee_id,
+            ":min": filter.min,
+            ":max": filter.max,
+            ":memo": filter.memo,
+            ":job_id": job_id,
+            ":uncategorized": filter.uncategorized,
+            ":status": filter.status,
+        })?
+        .and_then(|row| {
+            Ok(Transaction {
+                id: row.get(0)?,
+                date: row.get(1)?,
+                account: row.get(2)?,
+                splits: Vec::new(),
+                amount: row.get(3)?,
+                payee: row.get(4)?,
+                memo: row.get(5)?,
+                status: row.get(6)?,
+                transfer: row.get(7)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    for transaction in &mut transactions {
+        transaction.splits = split::select(conn, transaction.id)?;
+    }
+    Ok(transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        date::YearMonth,
+        db::{job::INFLOW_JOB_NAME, AccountType, Db},
+    };
+
+    fn balance(db: &mut Db, year_month: YearMonth) -> Result<(i64, i64)> {
+        let (_, balance) = db.select_month_balances(year_month)?.remove(0);
+        Ok((balance.activity, balance.balance))
+    }
+
+    #[test]
+    fn edit_and_delete_rewind_balances() -> Result<()> {
+        let mut db = Db::create_in_memory()?;
+        db.insert_account("checking".to_owned(), AccountType::Cash)?;
+        db.insert_job("groceries".to_owned())?;
+        let may = YearMonth {
+            year: 2022,
+            month: 4,
+        };
+        let june = YearMonth {
+            year: 2022,
+            month: 5,
+        };
+        let id = db.insert_transaction(&NewTransaction {
+            date: june.first_day(),
+            account: "checking".to_owned(),
+            amount: -500,
+            splits: vec![("groceries".to_owned(), -500)],
+            payee: String::new(),
+            memo: String::new(),
+            status: ClearedStatus::Uncleared,
+        })?;
+        assert_eq!(balance(&mut db, june)?, (-500, -500));
+
+        let edit = TransactionEdit {
+            date: Some(may.last_day()),
+            amount: Some(-300),
+            ..Default::default()
+        };
+        db.update_transaction(id, &edit)?;
+        assert_eq!(balance(&mut db, may)?, (-300, -300));
+        assert_eq!(balance(&mut db, june)?, (0, -300));
+
+        db.delete_transaction(id)?;
+        assert_eq!(balance(&mut db, may)?, (0, 0));
+        assert_eq!(balance(&mut db, june)?, (0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn flipping_the_sign_picks_the_job_again() -> Result<()> {
+        let mut db = Db::create_in_memory()?;
+        db.insert_account("checking".to_owned(), AccountType::Cash)?;
+        let id = db.insert_transaction(&NewTransaction {
+            date: NaiveDate::from_ymd_opt(2022, 6, 1).unwrap(),
+            account: "checking".to_owned(),
+            amount: 500,
+            splits: Vec::new(),
+            payee: String::new(),
+            memo: String::new(),
+            status: ClearedStatus::Uncleared,
+        })?;
+        let jobs = |db: &mut Db| -> Result<Vec<String>> {
+            Ok(db
+                .select_transactions(&Default::default())?
+                .remove(0)
+                .splits
+                .into_iter()
+                .map(|split| split.job)
+                .collect())
+        };
+        assert_eq!(jobs(&mut db)?, vec![INFLOW_JOB_NAME]);
+
+        let edit = |amount, job: Option<&str>| TransactionEdit {
+            amount: Some(amount),
+            job: job.map(str::to_owned),
+            ..Default::default()
+        };
+        db.update_transaction(id, &edit(-500, None))?;
+        assert!(jobs(&mut db)?.is_empty());
+        db.update_transaction(id, &edit(-300, None))?;
+        assert!(jobs(&mut db)?.is_empty());
+        db.update_transaction(id, &edit(300, None))?;
+        assert_eq!(jobs(&mut db)?, vec![INFLOW_JOB_NAME]);
+        db.update_transaction(id, &edit(-300, Some(INFLOW_JOB_NAME)))?;
+        assert_eq!(jobs(&mut db)?, vec![INFLOW_JOB_NAME]);
+        Ok(())
+    }
+
+    #[test]
+    fn credit_spending_moves_into_payment_job() -> Result<()> {
+        let mut db = Db::create_in_memory()?;
+        db.insert_account("checking".to_owned(), AccountType::Cash)?;
+        db.insert_account("visa".to_owned(), AccountType::Credit)?;
+        db.insert_job("groceries".to_owned())?;
+        let june = YearMonth {
+            year: 2022,
+            month: 5,
+        };
+        let payment = |db: &mut Db| -> Result<i64> {
+            let (_, balance) = db
+                .select_month_balances(june)?
+                .into_iter()
+                .find(|(job, _)| job.name == "visa Payment")
+                .unwrap();
+            Ok(balance.balance)
+        };
+        let id = db.insert_transaction(&NewTransaction {
+            date: june.first_day(),
+            account: "visa".to_owned(),
+            amount: -500,
+            splits: vec![("groceries".to_owned(), -500)],
+            payee: String::new(),
+            memo: String::new(),
+            status: ClearedStatus::Uncleared,
+        })?;
+        assert_eq!(payment(&mut db)?, 500);
+
+        db.update_transaction(
+            id,
+            &TransactionEdit {
+                account: Some("checking".to_owned()),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(payment(&mut db)?, 0);
+        db.update_transaction(
+            id,
+            &TransactionEdit {
+                account: Some("visa".to_owned()),
+                ..Default::default()
+            },
+        )?;
+
+        db.insert_transfer(&NewTransfer {
+            date: june.last_day(),
+            from: "checking".to_owned(),
+            to: "visa".to_owned(),
+            amount: 500,
+            job: None,
+            memo: String::new(),
+        })?;
+        assert_eq!(payment(&mut db)?, 0);
+
+        let transfer_id = db
+            .select_transactions(&TransactionFilter {
+                account: Some("visa".to_owned()),
+                min: Some(1),
+                ..Default::default()
+            })?
+            .remove(0)
+            .id;
+        db.delete_transaction(transfer_id)?;
+        assert_eq!(payment(&mut db)?, 500);
+        assert_eq!(db.select_transactions(&Default::default())?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn tracking_accounts_stay_off_budget() -> Result<()> {
+        let mut db = Db::create_in_memory()?;
+        db.insert_account("checking".to_owned(), AccountType::Cash)?;
+        db.insert_account("401k".to_owned(), AccountType::Tracking)?;
+        db.insert_job("retirement".to_owned())?;
+        let june = YearMonth {
+            year: 2022,
+            month: 5,
+        };
+        let balances = |db: &mut Db| -> Result<Vec<i64>> {
+            Ok(db
+                .select_budget(None, june)?
+                .into_iter()
+                .map(|(_, balance)| balance.balance)
+                .collect())
+        };
+        let growth = NewTransaction {
+            date: june.first_day(),
+            account: "401k".to_owned(),
+            amount: 10_000,
+            splits: Vec::new(),
+            payee: String::new(),
+            memo: String::new(),
+            status: ClearedStatus::Uncleared,
+        };
+        db.insert_transaction(&growth)?;
+        assert_eq!(balances(&mut db)?, vec![0, 0]);
+        let with_job = NewTransaction {
+            splits: vec![("retirement".to_owned(), 10_000)],
+            ..growth
+        };
+        assert!(db.insert_transaction(&with_job).is_err());
+
+        let contribution = NewTransfer {
+            date: june.first_day(),
+            from: "checking".to_owned(),
+            to: "401k".to_owned(),
+            amount: 500,
+            job: Some("retirement".to_owned()),
+            memo: String::new(),
+        };
+        db.insert_transfer(&contribution)?;
+        assert_eq!(balances(&mut db)?, vec![0, -500]);
+        let withdrawal = NewTransfer {
+            from: "401k".to_owned(),
+            to: "checking".to_owned(),
+            job: None,
+            ..contribution
+        };
+        db.insert_transfer(&withdrawal)?;
+        assert_eq!(balances(&mut db)?, vec![500, -500]);
+        Ok(())
+    }
+}
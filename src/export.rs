@@ -0,0 +1,178 @@
+//! Exports data as CSV or JSON for use in spreadsheets and other tools.
+//!
+//! The columns of each export are documented in docs/export.md.
+//! Amounts are always integer cents.
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{
+    date::YearMonth,
+    db::{AccountType, ClearedStatus, Db, TransactionFilter},
+};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum What {
+    /// One row per transaction
+    Transactions,
+    /// One row per job per month
+    Budget,
+    /// One row per account
+    Accounts,
+}
+
+/// A row of exported data.
+trait Row: Serialize {
+    /// Column names, in the order the fields are serialized.
+    /// CSV exports include these as a header even when there are no rows.
+    const COLUMNS: &'static [&'static str];
+}
+
+#[derive(Serialize)]
+struct TransactionRow {
+    id: i64,
+    date: String,
+    account: String,
+    job: Option<String>,
+    amount: i64,
+    memo: String,
+    payee: Option<String>,
+    status: ClearedStatus,
+    /// The other account of a transfer
+    transfer: Option<String>,
+}
+
+impl Row for TransactionRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "id", "date", "account", "job", "amount", "memo", "payee", "status", "transfer",
+    ];
+}
+
+#[derive(Serialize)]
+struct BudgetRow {
+    month: YearMonth,
+    job: String,
+    balance: i64,
+    assigned: i64,
+    activity: i64,
+}
+
+impl Row for BudgetRow {
+    const COLUMNS: &'static [&'static str] = &["month", "job", "balance", "assigned", "activity"];
+}
+
+#[derive(Serialize)]
+struct AccountRow {
+    account: String,
+    balance: i64,
+    #[serde(rename = "type")]
+    account_type: AccountType,
+}
+
+impl Row for AccountRow {
+    const COLUMNS: &'static [&'static str] = &["account", "balance", "type"];
+}
+
+/// Print the selected data to stdout.
+///
+/// `from` and `to` are inclusive. Budgets include every month that overlaps the range,
+/// and account balances are as of `to`.
+pub fn export(
+    db: &mut Db,
+    today: NaiveDate,
+    format: Format,
+    what: What,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Result<()> {
+    match what {
+        What::Transactions => {
+            let rows = db
+                .select_transactions(&TransactionFilter {
+                    from,
+                    to,
+                    ..Default::default()
+                })?
+                .into_iter()
+                .flat_map(|tx| {
+                    // Split transactions get one row per split
+                    let splits = if tx.splits.is_empty() {
+                        vec![(None, tx.amount)]
+                    } else {
+                        tx.splits
+                            .into_iter()
+                            .map(|split| (Some(split.job), split.amount))
+                            .collect()
+                    };
+                    splits.into_iter().map(move |(job, amount)| TransactionRow {
+                        id: tx.id,
+                        date: tx.date.to_string(),
+                        account: tx.account.clone(),
+                        job,
+                        amount,
+                        memo: tx.memo.clone(),
+                        payee: tx.payee.clone(),
+                        status: tx.status,
+                        transfer: tx.transfer.clone(),
+                    })
+                });
+            write(format, rows)
+        }
+        What::Budget => {
+            let from = from.map(YearMonth::from_date);
+            let to = YearMonth::from_date(to.unwrap_or(today));
+            let rows = db
+                .select_budget(from, to)?
+                .into_iter()
+                .map(|(job, balance)| BudgetRow {
+                    month: balance.year_month,
+                    job,
+                    balance: balance.balance,
+                    assigned: balance.assigned,
+                    activity: balance.activity,
+                });
+            write(format, rows)
+        }
+        What::Accounts => {
+            let rows = db
+                .select_account_balances(to)?
+                .into_iter()
+                .map(|(account, balance)| AccountRow {
+                    account: account.name,
+                    balance,
+                    account_type: account.account_type,
+                });
+            write(format, rows)
+        }
+    }
+}
+
+fn write<T: Row>(format: Format, rows: impl Iterator<Item = T>) -> Result<()> {
+    let stdout = std::io::stdout();
+    match format {
+        Format::Csv => {
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(stdout.lock());
+            writer.write_record(T::COLUMNS)?;
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            writer.flush()?;
+        }
+        Format::Json => {
+            let rows: Vec<T> = rows.collect();
+            serde_json::to_writer_pretty(stdout.lock(), &rows)?;
+            println!();
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,272 @@
+//! Projects account and job balances forward using schedules and goals.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{
+    amount::format_cents,
+    date::{format_month_year, YearMonth},
+    db::{AccountType, Db, JobBalance, OverspendingPolicy, TransactionFilter},
+    goals,
+    output::Output,
+    table::{Align, Table},
+};
+
+#[derive(Serialize)]
+struct Forecast {
+    /// Last day of the forecast, inclusive
+    to: String,
+    /// Upcoming transactions in chronological order
+    events: Vec<Event>,
+    /// The first time each cash account drops below zero
+    warnings: Vec<Warning>,
+    jobs: Vec<JobForecast>,
+}
+
+#[derive(Serialize)]
+struct Event {
+    date: String,
+    account: String,
+    payee: Option<String>,
+    job: Option<String>,
+    amount: i64,
+    /// The account's balance after this transaction
+    balance: i64,
+}
+
+#[derive(Serialize)]
+struct Warning {
+    account: String,
+    date: String,
+    balance: i64,
+}
+
+#[derive(Serialize)]
+struct JobForecast {
+    name: String,
+    months: Vec<MonthBalance>,
+}
+
+#[derive(Serialize)]
+struct MonthBalance {
+    month: YearMonth,
+    /// Cents assigned during the month, assuming goals are funded on time
+    assigned: i64,
+    /// Cents of scheduled activity during the month
+    activity: i64,
+    /// Running balance at the end of the month
+    balance: i64,
+}
+
+/// A transaction expected on or after today.
+struct Upcoming {
+    date: NaiveDate,
+    account: String,
+    payee: Option<String>,
+    job: Option<String>,
+    amount: i64,
+    /// False for transactions that are already recorded,
+    /// and so already part of this month's job balances.
+    scheduled: bool,
+}
+
+/// Forecast the current month and the next `months` months.
+///
+/// Accounts start at today's balance, and change with every scheduled occurrence
+/// that hasn't been recorded yet and every transaction dated after today.
+/// Jobs start at this month's balance. In later months, each job is assigned
+/// whatever its goal needs, and scheduled transactions in the job are its activity.
+/// Jobs that deduct overspending start each later month at zero or more.
+pub fn print_forecast(db: &mut Db, today: NaiveDate, months: u32, output: Output) -> Result<()> {
+    let this_month = YearMonth::from_date(today);
+    let last_month = YearMonth::from_int(this_month.to_int() + months as i64);
+    let to = last_month.last_day();
+    let upcoming = select_upcoming(db, today, to)?;
+
+    let mut balances = HashMap::new();
+    let mut cash_accounts = Vec::new();
+    for (account, balance) in db.select_account_balances(Some(today))? {
+        if account.account_type == AccountType::Cash {
+            cash_accounts.push(account.name.clone());
+        }
+        balances.insert(account.name, balance);
+    }
+    let mut events = Vec::new();
+    let mut warnings: Vec<Warning> = Vec::new();
+    for tx in &upcoming {
+        let balance = balances.entry(tx.account.clone()).or_insert(0);
+        *balance += tx.amount;
+        let already_warned = warnings.iter().any(|warning| warning.account == tx.account);
+        if *balance < 0 && !already_warned && cash_accounts.contains(&tx.account) {
+            warnings.push(Warning {
+                account: tx.account.clone(),
+                date: tx.date.to_string(),
+                balance: *balance,
+            });
+        }
+        events.push(Event {
+            date: tx.date.to_string(),
+            account: tx.account.clone(),
+            payee: tx.payee.clone(),
+            job: tx.job.clone(),
+            amount: tx.amount,
+            balance: *balance,
+        });
+    }
+
+    let goals = db.select_goals()?;
+    let jobs = db
+        .select_month_balances(this_month)?
+        .into_iter()
+        .map(|(job, current)| {
+            let goal = goals.iter().find(|goal| goal.job == job.name);
+            let mut months = Vec::new();
+            let mut balance = current.balance;
+            for year_month in (this_month.to_int()..=last_month.to_int()).map(YearMonth::from_int) {
+                if year_month != this_month && job.overspending == OverspendingPolicy::Deduct {
+                    balance = balance.max(0);
+                }
+                let activity: i64 = upcoming
+                    .iter()
+                    .filter(|tx| {
+                        YearMonth::from_date(tx.date) == year_month
+                            && tx.job.as_ref() == Some(&job.name)
+                            && (tx.scheduled || year_month != this_month)
+                    })
+                    .map(|tx| tx.amount)
+                    .sum();
+                let assigned = if year_month == this_month {
+                    current.assigned
+                } else {
+                    let start = JobBalance {
+                        year_month,
+                        job_id: 0,
+                        balance,
+                        assigned: 0,
+                        activity: 0,
+                    };
+                    goal.map_or(0, |goal| goals::progress(goal, &start).underfunded)
+                };
+                if year_month != this_month {
+                    balance += assigned;
+                }
+                balance += activity;
+                months.push(MonthBalance {
+                    month: year_month,
+                    assigned,
+                    activity,
+                    balance,
+                });
+            }
+            JobForecast {
+                name: job.name,
+                months,
+            }
+        })
+        .collect();
+
+    let forecast = Forecast {
+        to: to.to_string(),
+        events,
+        warnings,
+        jobs,
+    };
+    output.print(&forecast, print_text)
+}
+
+/// Every unrecorded schedule occurrence up to `to`, including overdue ones,
+/// and every transaction dated after today, in chronological order.
+fn select_upcoming(db: &mut Db, today: NaiveDate, to: NaiveDate) -> Result<Vec<Upcoming>> {
+    let mut upcoming: Vec<Upcoming> = db
+        .select_transactions(&TransactionFilter {
+            from: today.succ_opt(),
+            to: Some(to),
+            ..Default::default()
+        })?
+        .into_iter()
+        .map(|tx| Upcoming {
+            date: tx.date,
+            account: tx.account,
+            payee: tx.payee,
+            job: match tx.splits.as_slice() {
+                [split] => Some(split.job.clone()),
+                _ => None,
+            },
+            amount: tx.amount,
+            scheduled: false,
+        })
+        .collect();
+    for schedule in db.select_schedules()? {
+        let dates = (schedule.recorded..)
+            .map_while(|index| schedule.occurrence(index))
+            .take_while(|&date| date <= to);
+        for date in dates {
+            upcoming.push(Upcoming {
+                date,
+                account: schedule.account.clone(),
+                payee: schedule.payee.clone(),
+                job: schedule.job.clone(),
+                amount: schedule.amount,
+                scheduled: true,
+            });
+        }
+    }
+    // Stable, so recorded transactions come before scheduled ones on the same day
+    upcoming.sort_by_key(|tx| tx.date);
+    Ok(upcoming)
+}
+
+fn print_text(forecast: &Forecast) {
+    println!("[ Until {} ]", forecast.to);
+    if forecast.events.is_empty() {
+        println!("No upcoming transactions");
+    } else {
+        use Align::*;
+        let mut table = Table::new(&[Left, Left, Left, Left, Right, Right]);
+        table.push(
+            ["Date", "Account", "Payee", "Job", "Amount", "Balance"]
+                .map(str::to_owned)
+                .to_vec(),
+        );
+        for event in &forecast.events {
+            table.push(vec![
+                event.date.clone(),
+                event.account.clone(),
+                event.payee.clone().unwrap_or_default(),
+                event.job.clone().unwrap_or_default(),
+                format_cents(event.amount),
+                format_cents(event.balance),
+            ]);
+        }
+        table.print();
+    }
+    for warning in &forecast.warnings {
+        println!(
+            "Warning: {} drops to {} on {}",
+            warning.account,
+            format_cents(warning.balance),
+            warning.date
+        );
+    }
+    if forecast.jobs.is_empty() {
+        return;
+    }
+    println!();
+    let mut aligns = vec![Align::Left];
+    let mut header = vec![String::new()];
+    for month in &forecast.jobs[0].months {
+        aligns.push(Align::Right);
+        header.push(format_month_year(&month.month.first_day()));
+    }
+    let mut table = Table::new(&aligns);
+    table.push(header);
+    for job in &forecast.jobs {
+        let mut row = vec![job.name.clone()];
+        row.extend(job.months.iter().map(|month| format_cents(month.balance)));
+        table.push(row);
+    }
+    table.print();
+}
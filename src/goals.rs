@@ -0,0 +1,93 @@
+//! Measures how far along each job is toward its goal.
+
+use serde::Serialize;
+
+use crate::db::{Goal, GoalKind, JobBalance};
+
+#[derive(Serialize)]
+pub struct Progress {
+    /// Cents that still need to be assigned this month to stay on track.
+    pub underfunded: i64,
+    /// How much of the goal is funded, from 0 to 100.
+    pub percent: i64,
+}
+
+/// Compare a goal with its job's balance for a month.
+///
+/// Spending during the month doesn't count against a goal, since the money
+/// was there to be spent. A target goal with a month spreads what's left
+/// of the target evenly over the months until then.
+pub fn progress(goal: &Goal, balance: &JobBalance) -> Progress {
+    let carried_over = balance.balance - balance.assigned - balance.activity;
+    let (needed, funded) = match goal.kind {
+        GoalKind::Monthly => (goal.amount, balance.assigned),
+        GoalKind::Spending => (goal.amount - carried_over, carried_over + balance.assigned),
+        GoalKind::Target => {
+            let months_left = goal.by.map_or(1, |by| {
+                (by.to_int() - balance.year_month.to_int() + 1).max(1)
+            });
+            let remaining = (goal.amount - carried_over).max(0);
+            // Round up so that the target is reached on time
+            let needed = (remaining + months_left - 1) / months_left;
+            (needed, carried_over + balance.assigned)
+        }
+    };
+    Progress {
+        underfunded: (needed - balance.assigned).max(0),
+        percent: (funded * 100 / goal.amount).clamp(0, 100),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::YearMonth;
+
+    const JUNE: YearMonth = YearMonth {
+        year: 2022,
+        month: 5,
+    };
+
+    fn balance(carried_over: i64, assigned: i64, activity: i64) -> JobBalance {
+        JobBalance {
+            year_month: JUNE,
+            job_id: 1,
+            balance: carried_over + assigned + activity,
+            assigned,
+            activity,
+        }
+    }
+
+    fn goal(kind: GoalKind, amount: i64, by: Option<YearMonth>) -> Goal {
+        Goal {
+            job: "insurance".to_owned(),
+            kind,
+            amount,
+            by,
+        }
+    }
+
+    #[test]
+    fn target_by_month_spreads_the_remainder() {
+        let november = YearMonth {
+            year: 2022,
+            month: 10,
+        };
+        let goal = goal(GoalKind::Target, 1_000, Some(november));
+        let behind = progress(&goal, &balance(400, 0, 0));
+        assert_eq!(behind.underfunded, 100);
+        assert_eq!(behind.percent, 40);
+
+        let ahead = progress(&goal, &balance(400, 150, -50));
+        assert_eq!(ahead.underfunded, 0);
+        assert_eq!(ahead.percent, 55);
+    }
+
+    #[test]
+    fn spending_goal_counts_what_is_carried_over() {
+        let goal = goal(GoalKind::Spending, 300, None);
+        let short = progress(&goal, &balance(100, 50, -120));
+        assert_eq!(short.underfunded, 150);
+        assert_eq!(short.percent, 50);
+    }
+}
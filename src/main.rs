@@ -1,31 +1,56 @@
 // Dead code warnings cause tests to fail
 #![allow(dead_code)]
 
+mod accounts;
+mod amount;
+mod auto_assign;
+mod chart;
 mod date;
 mod db;
+mod export;
+mod forecast;
+mod goals;
+mod output;
+mod payees;
+mod reconcile;
+mod report;
+mod rules;
+mod schedules;
 mod status;
+mod table;
+mod templates;
+mod transactions;
 
 use std::env::VarError;
 
 use anyhow::{anyhow, Result};
 use chrono::NaiveDate;
-use clap::{Parser, Subcommand};
+use clap::{builder::RangedU64ValueParser, value_parser, Parser, Subcommand};
 
-use db::Db;
+use date::YearMonth;
+use db::{
+    AccountType, ClearedStatus, Db, Goal, GoalKind, NewTransaction, NewTransfer,
+    OverspendingPolicy, Repeat, Schedule, TransactionEdit, TransactionFilter,
+};
+use output::{ColorChoice, Output};
 
 fn main() -> Result<()> {
     let args = Args::parse();
     let today = date::init_date()?;
+    args.color.apply();
 
     let db_path = args.db.ok_or_else(|| std::env::var("TBB_DB_FILE"));
     let db_path = handle_db_path_err(db_path)?;
     let mut db = Db::create(db_path);
 
-    args.command.execute(&mut db, today)?;
+    args.command.execute(&mut db, today, args.output)?;
 
     Ok(())
 }
 
+/// The most months a --months option accepts, which keeps every month in range of a date.
+const MAX_MONTHS: i64 = 1200;
+
 fn handle_db_path_err(db_path: Result<String, Result<String, VarError>>) -> Result<String> {
     match db_path {
         Ok(str) | Err(Ok(str)) => Ok(str),
@@ -34,6 +59,13 @@ fn handle_db_path_err(db_path: Result<String, Result<String, VarError>>) -> Resu
     }
 }
 
+fn parse_split(str: &str) -> Result<(String, i64)> {
+    let (job_name, amount) = str
+        .rsplit_once('=')
+        .ok_or_else(|| anyhow!("expected JOB=AMOUNT"))?;
+    Ok((job_name.to_owned(), amount::parse_cents(amount)?))
+}
+
 #[derive(Parser)]
 #[command()]
 struct Args {
@@ -42,43 +74,479 @@ struct Args {
     /// Path to sqlite file; defaults to $TBB_DB_FILE
     #[arg(long)]
     pub db: Option<String>,
+    /// Output format of commands that print data
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub output: Output,
+    /// Whether to color text output
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub color: ColorChoice,
 }
 
 #[derive(Subcommand)]
 enum Command {
     /// Show one month's budget
-    Status,
+    Status {
+        /// Also show what each job spent last month, and on average over 3, 6 and 12 months
+        #[arg(long)]
+        averages: bool,
+    },
+    /// Project account and job balances forward using schedules and goals
+    Forecast {
+        /// How many months after the current one to include, up to 1200
+        #[arg(long, default_value_t = 3, value_parser = value_parser!(u32).range(0..=MAX_MONTHS))]
+        months: u32,
+    },
     #[clap(subcommand)]
     Account(AccountCommand),
     #[clap(subcommand)]
     Job(JobCommand),
+    #[clap(subcommand)]
+    Tx(TxCommand),
+    #[clap(subcommand)]
+    Rule(RuleCommand),
+    #[clap(subcommand)]
+    Payee(PayeeCommand),
+    #[clap(subcommand)]
+    Goal(GoalCommand),
+    #[clap(subcommand)]
+    Budget(BudgetCommand),
+    #[clap(subcommand)]
+    Template(TemplateCommand),
+    #[clap(subcommand)]
+    Schedule(ScheduleCommand),
+    #[clap(subcommand)]
+    Report(ReportCommand),
+    /// Assign money to a job
+    Assign {
+        #[arg(required_unless_present = "auto")]
+        job_name: Option<String>,
+        #[arg(
+            required_unless_present = "auto",
+            allow_hyphen_values = true,
+            value_parser = amount::parse_cents
+        )]
+        amount: Option<i64>,
+        /// Month to assign to, in yyyy-MM format; defaults to the current month
+        #[arg(long)]
+        month: Option<YearMonth>,
+        /// Assign to every job at once, until Ready to Assign runs out
+        #[arg(long, conflicts_with_all = ["job_name", "amount"])]
+        auto: bool,
+        /// How --auto decides what each job gets
+        #[arg(long, value_enum, default_value_t, requires = "auto")]
+        strategy: auto_assign::Strategy,
+        /// Show what --auto would assign without assigning it
+        #[arg(long, requires = "auto")]
+        dry_run: bool,
+    },
+    /// Match an account against a bank statement, then lock the matched transactions
+    Reconcile {
+        account_name: String,
+        /// Ending balance on the statement
+        #[arg(allow_hyphen_values = true, value_parser = amount::parse_cents)]
+        statement_balance: i64,
+        /// Book an adjustment transaction for any difference without asking
+        #[arg(long)]
+        adjust: bool,
+    },
+    /// Export data as CSV or JSON
+    Export {
+        format: export::Format,
+        #[arg(long)]
+        what: export::What,
+        /// First date to include, in yyyy-MM-dd format
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Last date to include, in yyyy-MM-dd format
+        #[arg(long)]
+        to: Option<NaiveDate>,
+    },
 }
 
 #[derive(Subcommand)]
 enum AccountCommand {
-    Add { account_name: String },
+    /// Add an account. Credit accounts also get a job for saving up their payments,
+    /// and tracking accounts are kept out of the budget
+    Add {
+        account_name: String,
+        #[arg(long = "type", value_enum, default_value_t)]
+        account_type: AccountType,
+    },
+    /// List accounts and their balances
+    List,
 }
 
 #[derive(Subcommand)]
 enum JobCommand {
-    Add { job_name: String },
+    Add {
+        job_name: String,
+    },
+    /// Put a job in a group, or take it out of its group if none is given
+    Group {
+        job_name: String,
+        group: Option<String>,
+    },
+    /// Choose what happens when a job ends a month with a negative balance
+    Overspending {
+        job_name: String,
+        policy: OverspendingPolicy,
+    },
+}
+
+#[derive(Subcommand)]
+enum TxCommand {
+    /// Record a transaction. Spending is negative, income is positive
+    Add {
+        account_name: String,
+        #[arg(allow_hyphen_values = true, value_parser = amount::parse_cents)]
+        amount: i64,
+        /// Defaults to Ready to Assign for income and uncategorized for spending
+        #[arg(long, conflicts_with = "split")]
+        job: Option<String>,
+        /// Put part of the amount in a job, e.g. --split groceries=-12.50.
+        /// Can be repeated, and splits must add up to the amount
+        #[arg(long, value_name = "JOB=AMOUNT", value_parser = parse_split)]
+        split: Vec<(String, i64)>,
+        /// Date in yyyy-MM-dd format; defaults to today
+        #[arg(long)]
+        date: Option<NaiveDate>,
+        /// Payee name or alias. Unknown payees are created
+        #[arg(long, default_value = "")]
+        payee: String,
+        #[arg(long, default_value = "")]
+        memo: String,
+        /// Mark the transaction as already cleared
+        #[arg(long)]
+        cleared: bool,
+    },
+    /// Move money between accounts, e.g. to pay off a credit card
+    Transfer {
+        from_account: String,
+        to_account: String,
+        #[arg(value_parser = amount::parse_cents)]
+        amount: i64,
+        /// The job to spend from when moving money into a tracking account
+        #[arg(long)]
+        job: Option<String>,
+        /// Date in yyyy-MM-dd format; defaults to today
+        #[arg(long)]
+        date: Option<NaiveDate>,
+        #[arg(long, default_value = "")]
+        memo: String,
+    },
+    /// Mark transactions as cleared
+    Clear {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+    /// Mark transactions as uncleared
+    Unclear {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+    /// Change a transaction. Options that are left out keep their old value
+    Edit {
+        id: i64,
+        #[arg(long)]
+        account: Option<String>,
+        #[arg(long, allow_hyphen_values = true, value_parser = amount::parse_cents)]
+        amount: Option<i64>,
+        /// Put the whole amount in this job
+        #[arg(long, conflicts_with_all = ["split", "uncategorized"])]
+        job: Option<String>,
+        /// Replace the splits. Can be repeated, and splits must add up to the amount
+        #[arg(long, value_name = "JOB=AMOUNT", value_parser = parse_split)]
+        split: Vec<(String, i64)>,
+        /// Remove the job
+        #[arg(long, conflicts_with = "split")]
+        uncategorized: bool,
+        /// Date in yyyy-MM-dd format
+        #[arg(long)]
+        date: Option<NaiveDate>,
+        #[arg(long)]
+        payee: Option<String>,
+        #[arg(long)]
+        memo: Option<String>,
+    },
+    /// Delete a transaction
+    Delete { id: i64 },
+    /// List transactions in chronological order
+    List {
+        #[arg(long)]
+        account: Option<String>,
+        /// Only show transactions with a split in this job
+        #[arg(long)]
+        job: Option<String>,
+        /// Payee name or alias
+        #[arg(long)]
+        payee: Option<String>,
+        /// First date to include, in yyyy-MM-dd format
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Last date to include, in yyyy-MM-dd format
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Smallest amount to include. Spending is negative
+        #[arg(long, allow_hyphen_values = true, value_parser = amount::parse_cents)]
+        min: Option<i64>,
+        /// Largest amount to include. Spending is negative
+        #[arg(long, allow_hyphen_values = true, value_parser = amount::parse_cents)]
+        max: Option<i64>,
+        /// Only show transactions whose memo contains this text, ignoring case
+        #[arg(long)]
+        memo: Option<String>,
+        /// Only show transactions without a job
+        #[arg(long, conflicts_with = "job")]
+        uncategorized: bool,
+        #[arg(long)]
+        status: Option<ClearedStatus>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PayeeCommand {
+    /// List payees with their default jobs and aliases
+    List,
+    /// Give a payee a new name. Its aliases are kept
+    Rename { old_name: String, new_name: String },
+    /// Merge one payee into another. The merged name becomes an alias
+    Merge {
+        from_name: String,
+        into_name: String,
+    },
+}
+
+/// Goals track what a job is saving up for. Each job has at most one goal
+#[derive(Subcommand)]
+enum GoalCommand {
+    /// Give a job a goal, replacing its old one
+    Set {
+        job_name: String,
+        kind: GoalKind,
+        #[arg(value_parser = amount::parse_cents)]
+        amount: i64,
+        /// Month to reach a target by, in yyyy-MM format
+        #[arg(long)]
+        by: Option<YearMonth>,
+    },
+    /// Remove a job's goal
+    Clear { job_name: String },
+}
+
+#[derive(Subcommand)]
+enum BudgetCommand {
+    /// Make every job's assigned amount in one month the same as in another
+    Copy {
+        /// Month to copy, in yyyy-MM format; defaults to the month before --to
+        #[arg(long)]
+        from: Option<YearMonth>,
+        /// Month to change, in yyyy-MM format; defaults to the current month
+        #[arg(long)]
+        to: Option<YearMonth>,
+    },
+}
+
+/// Templates are named sets of assigned amounts that can be applied to any month
+#[derive(Subcommand)]
+enum TemplateCommand {
+    /// Save a month's assigned amounts, replacing any template with the same name
+    Save {
+        name: String,
+        /// Month to save, in yyyy-MM format; defaults to the current month
+        #[arg(long)]
+        month: Option<YearMonth>,
+    },
+    /// Set the assigned amount of every job in a template
+    Apply {
+        name: String,
+        /// Month to change, in yyyy-MM format; defaults to the current month
+        #[arg(long)]
+        month: Option<YearMonth>,
+    },
+    /// List templates and their amounts
+    List,
+}
+
+/// Schedules record transactions that repeat, such as rent or a salary
+#[derive(Subcommand)]
+enum ScheduleCommand {
+    /// Add a schedule. Nothing is recorded until tbb schedule run
+    Add {
+        account_name: String,
+        #[arg(allow_hyphen_values = true, value_parser = amount::parse_cents)]
+        amount: i64,
+        #[arg(long, value_enum)]
+        repeat: Repeat,
+        /// Repeat every this many weeks, months or years
+        #[arg(long, default_value_t = 1)]
+        every: u32,
+        /// Date of the first occurrence in yyyy-MM-dd format; defaults to today.
+        /// Monthly and yearly schedules keep its day of the month
+        #[arg(long)]
+        start: Option<NaiveDate>,
+        /// Date after which the schedule stops, in yyyy-MM-dd format
+        #[arg(long)]
+        end: Option<NaiveDate>,
+        /// Defaults to categorizing like tbb tx add without a job
+        #[arg(long)]
+        job: Option<String>,
+        #[arg(long)]
+        payee: Option<String>,
+        #[arg(long, default_value = "")]
+        memo: String,
+    },
+    /// List schedules with the date each one is next due
+    List,
+    /// Record every occurrence that is due up to today
+    Run,
+    /// Delete a schedule. Transactions it already recorded are kept
+    Delete { id: i64 },
+}
+
+/// Reports summarize past transactions
+#[derive(Subcommand)]
+enum ReportCommand {
+    /// Total up spending between two dates, with each part's share of the total
+    Spending {
+        /// First date to include, in yyyy-MM-dd format
+        #[arg(long)]
+        from: NaiveDate,
+        /// Last date to include, in yyyy-MM-dd format
+        #[arg(long)]
+        to: NaiveDate,
+        #[arg(long, value_enum, default_value_t)]
+        group_by: report::GroupBy,
+    },
+    /// Compare income with spending for each month, with the share of income saved
+    IncomeExpense {
+        /// How many months to include, ending with the current one, up to 1200
+        #[arg(long, default_value_t = 12, value_parser = value_parser!(u32).range(1..=MAX_MONTHS))]
+        months: u32,
+    },
+    /// Sum the balances of every account, including credit and tracking accounts
+    NetWorth {
+        /// Show the total at the end of every month instead of each account today
+        #[arg(long)]
+        monthly: bool,
+    },
+    /// How many days, on average, money was held before it was spent
+    AgeOfMoney {
+        /// How many of the latest outflows to average over, at least 1
+        #[arg(
+            long,
+            default_value_t = report::DEFAULT_OUTFLOWS,
+            value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        outflows: usize,
+    },
+    /// Chart how much each job spent in each month
+    Trends {
+        /// How many months to include, ending with the current one, up to 1200
+        #[arg(long, default_value_t = 12, value_parser = value_parser!(u32).range(1..=MAX_MONTHS))]
+        months: u32,
+    },
+    /// Compare what was assigned to each job with what it spent
+    Variance {
+        /// Month to compare, in yyyy-MM format; defaults to the current month
+        #[arg(long)]
+        month: Option<YearMonth>,
+        /// How many months, ending with --month, to check for jobs that are often over budget,
+        /// from 1 to 1200
+        #[arg(long, default_value_t = 3, value_parser = value_parser!(u32).range(1..=MAX_MONTHS))]
+        months: u32,
+    },
+}
+
+/// Rules categorize transactions recorded without a job, based on their payee
+#[derive(Subcommand)]
+enum RuleCommand {
+    /// Add a rule. Rules are checked in the order they were added
+    Add {
+        /// Regular expression matched against the payee, e.g. '(?i)^amzn'
+        #[arg(long)]
+        payee_regex: String,
+        #[arg(long)]
+        job: String,
+        /// Only apply the rule to transactions in this account
+        #[arg(long)]
+        account: Option<String>,
+    },
+    /// List rules in the order they are checked
+    List,
+    /// Show which rule would categorize a payee
+    Test {
+        payee: String,
+        /// The account of the transaction. Without it, rules limited to an account don't match
+        #[arg(long)]
+        account: Option<String>,
+    },
 }
 
 impl Command {
-    fn execute(&self, db: &mut Db, today: NaiveDate) -> Result<()> {
+    fn execute(&self, db: &mut Db, today: NaiveDate, output: Output) -> Result<()> {
         match self {
-            Self::Status => status::print_status(db, today)?,
-            Self::Account(account_command) => account_command.execute(db)?,
+            Self::Status { averages } => status::print_status(db, today, *averages, output)?,
+            Self::Forecast { months } => forecast::print_forecast(db, today, *months, output)?,
+            Self::Account(account_command) => account_command.execute(db, output)?,
             Self::Job(job_command) => job_command.execute(db)?,
+            Self::Tx(tx_command) => tx_command.execute(db, today, output)?,
+            Self::Rule(rule_command) => rule_command.execute(db, output)?,
+            Self::Payee(payee_command) => payee_command.execute(db, output)?,
+            Self::Goal(goal_command) => goal_command.execute(db)?,
+            Self::Budget(budget_command) => budget_command.execute(db, today)?,
+            Self::Template(template_command) => template_command.execute(db, today, output)?,
+            Self::Schedule(schedule_command) => schedule_command.execute(db, today, output)?,
+            Self::Report(report_command) => report_command.execute(db, today, output)?,
+            Self::Assign {
+                job_name,
+                amount,
+                month,
+                auto,
+                strategy,
+                dry_run,
+            } => {
+                let month = month.unwrap_or_else(|| YearMonth::from_date(today));
+                match (job_name, amount) {
+                    (Some(job_name), Some(amount)) if !auto => {
+                        db.assign(job_name, month, *amount)?
+                    }
+                    _ => auto_assign::auto_assign(db, month, *strategy, *dry_run, output)?,
+                }
+            }
+            Self::Reconcile {
+                account_name,
+                statement_balance,
+                adjust,
+            } => reconcile::reconcile(
+                db,
+                &reconcile::Reconciliation {
+                    account: account_name.clone(),
+                    statement_balance: *statement_balance,
+                    adjust: *adjust,
+                    today,
+                },
+                &mut std::io::stdin().lock(),
+                &mut std::io::stdout().lock(),
+            )?,
+            Self::Export {
+                format,
+                what,
+                from,
+                to,
+            } => export::export(db, today, *format, *what, *from, *to)?,
         }
         Ok(())
     }
 }
 
 impl AccountCommand {
-    fn execute(&self, db: &mut Db) -> Result<()> {
+    fn execute(&self, db: &mut Db, output: Output) -> Result<()> {
         match self {
-            Self::Add { account_name } => db.insert_account(account_name.clone()),
+            Self::Add {
+                account_name,
+                account_type,
+            } => db.insert_account(account_name.clone(), *account_type),
+            Self::List => accounts::print_accounts(db, output),
         }
     }
 }
@@ -87,6 +555,247 @@ impl JobCommand {
     fn execute(&self, db: &mut Db) -> Result<()> {
         match self {
             Self::Add { job_name } => db.insert_job(job_name.clone()),
+            Self::Group { job_name, group } => db.set_job_group(job_name, group.as_deref()),
+            Self::Overspending { job_name, policy } => {
+                db.set_overspending_policy(job_name, *policy)
+            }
+        }
+    }
+}
+
+impl TxCommand {
+    fn execute(&self, db: &mut Db, today: NaiveDate, output: Output) -> Result<()> {
+        match self {
+            Self::Add {
+                account_name,
+                amount,
+                job,
+                split,
+                date,
+                payee,
+                memo,
+                cleared,
+            } => {
+                let splits = match job {
+                    Some(job) => vec![(job.clone(), *amount)],
+                    None => split.clone(),
+                };
+                db.insert_transaction(&NewTransaction {
+                    date: date.unwrap_or(today),
+                    account: account_name.clone(),
+                    amount: *amount,
+                    splits,
+                    payee: payee.clone(),
+                    memo: memo.clone(),
+                    status: if *cleared {
+                        ClearedStatus::Cleared
+                    } else {
+                        ClearedStatus::Uncleared
+                    },
+                })?;
+            }
+            Self::Transfer {
+                from_account,
+                to_account,
+                amount,
+                job,
+                date,
+                memo,
+            } => db.insert_transfer(&NewTransfer {
+                date: date.unwrap_or(today),
+                from: from_account.clone(),
+                to: to_account.clone(),
+                amount: *amount,
+                job: job.clone(),
+                memo: memo.clone(),
+            })?,
+            Self::Clear { ids } => {
+                for id in ids {
+                    db.update_cleared_status(*id, ClearedStatus::Cleared)?;
+                }
+            }
+            Self::Unclear { ids } => {
+                for id in ids {
+                    db.update_cleared_status(*id, ClearedStatus::Uncleared)?;
+                }
+            }
+            Self::Edit {
+                id,
+                account,
+                amount,
+                job,
+                split,
+                uncategorized,
+                date,
+                payee,
+                memo,
+            } => {
+                let edit = TransactionEdit {
+                    date: *date,
+                    account: account.clone(),
+                    amount: *amount,
+                    job: job.clone(),
+                    splits: split.clone(),
+                    uncategorized: *uncategorized,
+                    payee: payee.clone(),
+                    memo: memo.clone(),
+                };
+                db.update_transaction(*id, &edit)?;
+            }
+            Self::Delete { id } => db.delete_transaction(*id)?,
+            Self::List {
+                account,
+                job,
+                payee,
+                from,
+                to,
+                min,
+                max,
+                memo,
+                uncategorized,
+                status,
+            } => {
+                let filter = TransactionFilter {
+                    account: account.clone(),
+                    job: job.clone(),
+                    payee: payee.clone(),
+                    from: *from,
+                    to: *to,
+                    min: *min,
+                    max: *max,
+                    memo: memo.clone(),
+                    uncategorized: *uncategorized,
+                    status: *status,
+                };
+                transactions::print_transactions(db, &filter, output)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PayeeCommand {
+    fn execute(&self, db: &mut Db, output: Output) -> Result<()> {
+        match self {
+            Self::List => payees::print_payees(db, output),
+            Self::Rename { old_name, new_name } => db.rename_payee(old_name, new_name.clone()),
+            Self::Merge {
+                from_name,
+                into_name,
+            } => db.merge_payees(from_name, into_name),
+        }
+    }
+}
+
+impl GoalCommand {
+    fn execute(&self, db: &mut Db) -> Result<()> {
+        match self {
+            Self::Set {
+                job_name,
+                kind,
+                amount,
+                by,
+            } => db.set_goal(&Goal {
+                job: job_name.clone(),
+                kind: *kind,
+                amount: *amount,
+                by: *by,
+            }),
+            Self::Clear { job_name } => db.clear_goal(job_name),
+        }
+    }
+}
+
+impl BudgetCommand {
+    fn execute(&self, db: &mut Db, today: NaiveDate) -> Result<()> {
+        match self {
+            Self::Copy { from, to } => {
+                let to = to.unwrap_or_else(|| YearMonth::from_date(today));
+                let from = from.unwrap_or_else(|| YearMonth::from_int(to.to_int() - 1));
+                db.copy_budget(from, to)
+            }
+        }
+    }
+}
+
+impl TemplateCommand {
+    fn execute(&self, db: &mut Db, today: NaiveDate, output: Output) -> Result<()> {
+        let this_month = YearMonth::from_date(today);
+        match self {
+            Self::Save { name, month } => db.save_template(name, month.unwrap_or(this_month)),
+            Self::Apply { name, month } => db.apply_template(name, month.unwrap_or(this_month)),
+            Self::List => templates::print_templates(db, output),
+        }
+    }
+}
+
+impl ScheduleCommand {
+    fn execute(&self, db: &mut Db, today: NaiveDate, output: Output) -> Result<()> {
+        match self {
+            Self::Add {
+                account_name,
+                amount,
+                repeat,
+                every,
+                start,
+                end,
+                job,
+                payee,
+                memo,
+            } => db.insert_schedule(&Schedule {
+                id: 0,
+                account: account_name.clone(),
+                amount: *amount,
+                payee: payee.clone(),
+                job: job.clone(),
+                memo: memo.clone(),
+                repeat: *repeat,
+                every: *every,
+                start: start.unwrap_or(today),
+                end: *end,
+                recorded: 0,
+            }),
+            Self::List => schedules::print_schedules(db, output),
+            Self::Run => schedules::run_schedules(db, today, output),
+            Self::Delete { id } => db.delete_schedule(*id),
+        }
+    }
+}
+
+impl ReportCommand {
+    fn execute(&self, db: &mut Db, today: NaiveDate, output: Output) -> Result<()> {
+        match self {
+            Self::Spending { from, to, group_by } => {
+                report::print_spending(db, *from, *to, *group_by, output)
+            }
+            Self::IncomeExpense { months } => {
+                report::print_income_expense(db, today, *months, output)
+            }
+            Self::NetWorth { monthly } => report::print_net_worth(db, today, *monthly, output),
+            Self::AgeOfMoney { outflows } => {
+                report::print_age_of_money(db, today, *outflows, output)
+            }
+            Self::Trends { months } => report::print_trends(db, today, *months, output),
+            Self::Variance { month, months } => {
+                let month = month.unwrap_or_else(|| YearMonth::from_date(today));
+                report::print_variance(db, month, *months, output)
+            }
+        }
+    }
+}
+
+impl RuleCommand {
+    fn execute(&self, db: &mut Db, output: Output) -> Result<()> {
+        match self {
+            Self::Add {
+                payee_regex,
+                job,
+                account,
+            } => db.insert_rule(payee_regex.clone(), job, account.as_deref()),
+            Self::List => rules::print_rules(db, output),
+            Self::Test { payee, account } => {
+                rules::print_rule_test(db, payee, account.as_deref(), output)
+            }
         }
     }
 }
@@ -0,0 +1,65 @@
+//! Chooses between human-readable and machine-readable output.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum Output {
+    /// Tables and text meant for people
+    #[default]
+    Text,
+    /// Structured JSON meant for scripts. Amounts are integer cents
+    Json,
+}
+
+impl Output {
+    /// Print `value` as JSON, or call `print_text` to print it as text.
+    pub fn print<T: Serialize>(self, value: &T, print_text: impl FnOnce(&T)) -> Result<()> {
+        match self {
+            Output::Text => print_text(value),
+            Output::Json => {
+                serde_json::to_writer_pretty(std::io::stdout().lock(), value)?;
+                println!();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether text output is colored.
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum ColorChoice {
+    /// Color when printing to a terminal, unless NO_COLOR is set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Make everything printed from now on follow this choice.
+    pub fn apply(self) {
+        match self {
+            // colored already checks NO_COLOR and whether stdout is a terminal
+            ColorChoice::Auto => {}
+            ColorChoice::Always => colored::control::set_override(true),
+            ColorChoice::Never => colored::control::set_override(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use colored::Colorize;
+
+    use super::*;
+
+    #[test]
+    fn always_colors_without_a_terminal() {
+        ColorChoice::Always.apply();
+        let red = "overspent".red().to_string();
+        colored::control::unset_override();
+        assert!(red.starts_with("\x1b["), "{red:?}");
+    }
+}
@@ -0,0 +1,34 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{db::Db, output::Output};
+
+#[derive(Serialize)]
+struct PayeeStatus {
+    name: String,
+    job: Option<String>,
+    aliases: Vec<String>,
+}
+
+pub fn print_payees(db: &mut Db, output: Output) -> Result<()> {
+    let payees: Vec<PayeeStatus> = db
+        .select_payees()?
+        .into_iter()
+        .map(|payee| PayeeStatus {
+            name: payee.name,
+            job: payee.job,
+            aliases: payee.aliases,
+        })
+        .collect();
+    output.print(&payees, |payees| {
+        for payee in payees {
+            match &payee.job {
+                Some(job) => println!("{} -> {}", payee.name, job),
+                None => println!("{}", payee.name),
+            }
+            for alias in &payee.aliases {
+                println!("  aka {alias}");
+            }
+        }
+    })
+}
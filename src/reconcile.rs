@@ -0,0 +1,179 @@
+//! Walks through an account's uncleared transactions to match it against a bank statement.
+
+use std::io::{BufRead, Write};
+
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use crate::{
+    amount::format_cents,
+    db::{ClearedStatus, Db, NewTransaction, TransactionFilter},
+};
+
+pub struct Reconciliation {
+    pub account: String,
+    /// Cents
+    pub statement_balance: i64,
+    /// Book an adjustment for any remaining difference without asking.
+    pub adjust: bool,
+    /// Date of the adjustment transaction, if there is one.
+    pub today: NaiveDate,
+}
+
+/// Ask about each uncleared transaction in turn, then lock the cleared ones
+/// if the cleared balance matches the statement.
+///
+/// Answers are read line by line from `input`, and running out of input
+/// counts as quitting. Transactions marked as cleared stay cleared even
+/// if the reconciliation doesn't finish.
+pub fn reconcile(
+    db: &mut Db,
+    reconciliation: &Reconciliation,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> Result<()> {
+    let account = &reconciliation.account;
+    let mut cleared_balance = db.select_cleared_balance(account)?;
+    let uncleared = db.select_transactions(&TransactionFilter {
+        account: Some(account.clone()),
+        status: Some(ClearedStatus::Uncleared),
+        ..Default::default()
+    })?;
+    writeln!(
+        output,
+        "Statement balance: {}",
+        format_cents(reconciliation.statement_balance)
+    )?;
+    for transaction in uncleared {
+        let difference = reconciliation.statement_balance - cleared_balance;
+        if difference == 0 {
+            break;
+        }
+        writeln!(output, "Difference: {}", format_cents(difference))?;
+        let line = format!(
+            "{}  {}  {}  {}",
+            transaction.date,
+            transaction.payee.as_deref().unwrap_or(""),
+            format_cents(transaction.amount),
+            transaction.memo,
+        );
+        writeln!(output, "{}", line.trim_end())?;
+        match ask(input, output, "Cleared? [y/n/q] ")?.as_deref() {
+            Some("y") => {
+                db.update_cleared_status(transaction.id, ClearedStatus::Cleared)?;
+                cleared_balance += transaction.amount;
+            }
+            Some("n") => {}
+            _ => break,
+        }
+    }
+
+    let difference = reconciliation.statement_balance - cleared_balance;
+    if difference != 0 {
+        writeln!(output, "Difference: {}", format_cents(difference))?;
+        let adjust = reconciliation.adjust
+            || ask(input, output, "Book an adjustment transaction? [y/n] ")?.as_deref()
+                == Some("y");
+        if !adjust {
+            writeln!(output, "Not reconciled")?;
+            return Ok(());
+        }
+        db.insert_transaction(&NewTransaction {
+            date: reconciliation.today,
+            account: account.clone(),
+            amount: difference,
+            splits: Vec::new(),
+            payee: String::new(),
+            memo: "Reconciliation adjustment".to_owned(),
+            status: ClearedStatus::Cleared,
+        })?;
+        writeln!(
+            output,
+            "Booked an adjustment of {}",
+            format_cents(difference)
+        )?;
+    }
+    let count = db.reconcile_cleared(account)?;
+    writeln!(output, "Reconciled {count} transaction(s)")?;
+    Ok(())
+}
+
+/// Print a question and read one answer, trimmed and lowercased.
+/// Returns None at the end of input.
+fn ask(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    question: &str,
+) -> Result<Option<String>> {
+    write!(output, "{question}")?;
+    output.flush()?;
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        // End the question's line, since no answer was echoed
+        writeln!(output)?;
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::AccountType;
+
+    fn add(db: &mut Db, amount: i64) -> Result<i64> {
+        db.insert_transaction(&NewTransaction {
+            date: NaiveDate::from_ymd_opt(2022, 6, 1).unwrap(),
+            account: "checking".to_owned(),
+            amount,
+            splits: Vec::new(),
+            payee: String::new(),
+            memo: String::new(),
+            status: ClearedStatus::Uncleared,
+        })
+    }
+
+    fn run(db: &mut Db, statement_balance: i64, answers: &str) -> Result<String> {
+        let reconciliation = Reconciliation {
+            account: "checking".to_owned(),
+            statement_balance,
+            adjust: false,
+            today: NaiveDate::from_ymd_opt(2022, 6, 30).unwrap(),
+        };
+        let mut output = Vec::new();
+        reconcile(db, &reconciliation, &mut answers.as_bytes(), &mut output)?;
+        Ok(String::from_utf8(output)?)
+    }
+
+    #[test]
+    fn matching_statement_locks_cleared_transactions() -> Result<()> {
+        let mut db = Db::create_in_memory()?;
+        db.insert_account("checking".to_owned(), AccountType::Cash)?;
+        let salary = add(&mut db, 10_000)?;
+        add(&mut db, -3_000)?;
+        let rent = add(&mut db, -5_000)?;
+
+        let output = run(&mut db, 5_000, "y\nn\ny\n")?;
+        assert!(output.ends_with("Reconciled 2 transaction(s)\n"));
+        assert!(db.delete_transaction(salary).is_err());
+        assert!(db.delete_transaction(rent).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn unmatched_statement_can_be_adjusted() -> Result<()> {
+        let mut db = Db::create_in_memory()?;
+        db.insert_account("checking".to_owned(), AccountType::Cash)?;
+        add(&mut db, 10_000)?;
+
+        let output = run(&mut db, 9_000, "y\nn\n")?;
+        assert!(output.ends_with("Not reconciled\n"));
+        assert_eq!(db.select_cleared_balance("checking")?, 10_000);
+
+        let output = run(&mut db, 9_000, "y\n")?;
+        assert!(output.contains("Booked an adjustment of -10.00"));
+        assert!(output.ends_with("Reconciled 2 transaction(s)\n"));
+        assert_eq!(db.select_cleared_balance("checking")?, 9_000);
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+//! Summaries of past transactions, for looking back at where the money went.
+
+mod age_of_money;
+mod income_expense;
+mod net_worth;
+mod spending;
+mod trends;
+mod variance;
+
+pub use age_of_money::{age_of_money, print_age_of_money, DEFAULT_OUTFLOWS};
+pub use income_expense::print_income_expense;
+pub use net_worth::print_net_worth;
+pub use spending::{print_spending, GroupBy};
+pub use trends::print_trends;
+pub use variance::print_variance;
+
+/// A share of a total, rounded to a whole percent. 0 if the total isn't positive.
+fn percent(amount: i64, total: i64) -> i64 {
+    if total > 0 {
+        (amount * 100 + total / 2).div_euclid(total)
+    } else {
+        0
+    }
+}
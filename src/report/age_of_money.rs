@@ -0,0 +1,175 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{
+    amount::format_cents,
+    db::Db,
+    output::Output,
+    table::{Align, Table},
+};
+
+/// How many of the latest outflows the age of money is averaged over by default.
+pub const DEFAULT_OUTFLOWS: usize = 10;
+
+#[derive(Serialize)]
+struct Report {
+    /// Days. None if nothing has been spent yet
+    age_of_money: Option<i64>,
+    /// The outflows averaged over, oldest first
+    outflows: Vec<Outflow>,
+}
+
+#[derive(Serialize)]
+struct Outflow {
+    date: String,
+    payee: Option<String>,
+    job: Option<String>,
+    /// Cents spent
+    amount: i64,
+    /// Average days the money spent was held for
+    age: i64,
+}
+
+/// The average age of the money spent in the latest `count` outflows up to today, in days.
+/// None if nothing has been spent yet.
+pub fn age_of_money(db: &mut Db, today: NaiveDate, count: usize) -> Result<Option<i64>> {
+    Ok(average(&select_outflows(db, today, count)?))
+}
+
+pub fn print_age_of_money(
+    db: &mut Db,
+    today: NaiveDate,
+    count: usize,
+    output: Output,
+) -> Result<()> {
+    let outflows = select_outflows(db, today, count)?;
+    let report = Report {
+        age_of_money: average(&outflows),
+        outflows,
+    };
+    output.print(&report, print_text)
+}
+
+/// Select the latest `count` outflows up to today, with the age of each.
+///
+/// Money is spent first in, first out: each outflow uses up the oldest income
+/// that hasn't been spent yet. Spending that income doesn't cover yet has an age of 0.
+/// Refunds are left out, and so is spending that isn't counted in the spending report.
+fn select_outflows(db: &mut Db, today: NaiveDate, count: usize) -> Result<Vec<Outflow>> {
+    let first_date = match db.select_first_date()? {
+        Some(date) => date,
+        None => return Ok(Vec::new()),
+    };
+    let spending: Vec<_> = db
+        .select_spending(first_date, today)?
+        .into_iter()
+        .filter(|spending| spending.amount > 0)
+        .collect();
+    let dated: Vec<(NaiveDate, i64)> = spending
+        .iter()
+        .map(|spending| (spending.date, spending.amount))
+        .collect();
+    let ages = ages(&db.select_inflows(today)?, &dated);
+    let skip = spending.len().saturating_sub(count);
+    Ok(spending
+        .into_iter()
+        .zip(ages)
+        .skip(skip)
+        .map(|(spending, age)| Outflow {
+            date: spending.date.to_string(),
+            payee: spending.payee,
+            job: spending.job,
+            amount: spending.amount,
+            age,
+        })
+        .collect())
+}
+
+/// Match outflows with the inflows they spend, first in, first out,
+/// and return the age of each outflow in days. Both must be in date order,
+/// and outflow amounts must be positive.
+fn ages(inflows: &[(NaiveDate, i64)], outflows: &[(NaiveDate, i64)]) -> Vec<i64> {
+    let mut inflows = inflows.iter().copied().peekable();
+    let mut unspent: VecDeque<(NaiveDate, i64)> = VecDeque::new();
+    outflows
+        .iter()
+        .map(|&(date, amount)| {
+            while let Some(inflow) = inflows.next_if(|&(inflow_date, _)| inflow_date <= date) {
+                unspent.push_back(inflow);
+            }
+            let mut left = amount;
+            let mut cent_days = 0;
+            while let Some((inflow_date, inflow_left)) = unspent.front_mut().filter(|_| left > 0) {
+                let spent = left.min(*inflow_left);
+                cent_days += spent * (date - *inflow_date).num_days();
+                left -= spent;
+                *inflow_left -= spent;
+                if *inflow_left == 0 {
+                    unspent.pop_front();
+                }
+            }
+            cent_days / amount
+        })
+        .collect()
+}
+
+fn average(outflows: &[Outflow]) -> Option<i64> {
+    if outflows.is_empty() {
+        None
+    } else {
+        let total: i64 = outflows.iter().map(|outflow| outflow.age).sum();
+        Some(total / outflows.len() as i64)
+    }
+}
+
+fn print_text(report: &Report) {
+    let age = match report.age_of_money {
+        Some(age) => age,
+        None => {
+            println!("Nothing has been spent yet");
+            return;
+        }
+    };
+    println!(
+        "Age of money: {age} days, over the last {} outflow(s)",
+        report.outflows.len()
+    );
+    use Align::*;
+    let mut table = Table::new(&[Left, Left, Left, Right, Right]);
+    table.push(
+        ["Date", "Payee", "Job", "Amount", "Age"]
+            .map(str::to_owned)
+            .to_vec(),
+    );
+    for outflow in &report.outflows {
+        table.push(vec![
+            outflow.date.clone(),
+            outflow.payee.clone().unwrap_or_default(),
+            outflow.job.clone().unwrap_or_default(),
+            format_cents(outflow.amount),
+            outflow.age.to_string(),
+        ]);
+    }
+    table.print();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn june(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2022, 6, day).unwrap()
+    }
+
+    #[test]
+    fn oldest_money_is_spent_first() {
+        let inflows = [(june(1), 1_000), (june(11), 1_000)];
+        let outflows = [(june(5), 500), (june(21), 1_000), (june(21), 1_000)];
+        // The second outflow spends 500 from June 1st and 500 from June 11th,
+        // and the last one spends 500 from June 11th and 500 that hasn't arrived yet.
+        assert_eq!(ages(&inflows, &outflows), vec![4, 15, 5]);
+    }
+}
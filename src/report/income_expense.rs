@@ -0,0 +1,112 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{
+    amount::format_cents,
+    chart,
+    date::YearMonth,
+    db::Db,
+    output::Output,
+    table::{Align, Table},
+};
+
+#[derive(Serialize)]
+struct Report {
+    months: Vec<Month>,
+    total: Month,
+}
+
+#[derive(Serialize)]
+struct Month {
+    /// None for the total over every month
+    month: Option<YearMonth>,
+    /// Cents of income
+    income: i64,
+    /// Cents spent, less refunds, as in the spending report
+    expenses: i64,
+    /// Income minus expenses
+    net: i64,
+    /// Net as a share of income, up to 100. None without income
+    savings_rate: Option<i64>,
+}
+
+impl Month {
+    fn new(month: Option<YearMonth>, income: i64, expenses: i64) -> Month {
+        let net = income - expenses;
+        Month {
+            month,
+            income,
+            expenses,
+            net,
+            savings_rate: (income > 0).then(|| super::percent(net, income)),
+        }
+    }
+}
+
+/// Compare income with expenses for each of the last `months` months,
+/// up to and including the current one.
+pub fn print_income_expense(
+    db: &mut Db,
+    today: NaiveDate,
+    months: u32,
+    output: Output,
+) -> Result<()> {
+    let this_month = YearMonth::from_date(today).to_int();
+    let first_month = YearMonth::from_int(this_month + 1 - months as i64);
+    let mut expenses = vec![0; months as usize];
+    for spending in db.select_spending(
+        first_month.first_day(),
+        YearMonth::from_int(this_month).last_day(),
+    )? {
+        let index = YearMonth::from_date(spending.date).to_int() - first_month.to_int();
+        expenses[index as usize] += spending.amount;
+    }
+    let months = (first_month.to_int()..=this_month)
+        .map(YearMonth::from_int)
+        .zip(expenses)
+        .map(|(month, expenses)| Ok(Month::new(Some(month), db.select_income(month)?, expenses)))
+        .collect::<Result<Vec<Month>>>()?;
+    let total = Month::new(
+        None,
+        months.iter().map(|month| month.income).sum(),
+        months.iter().map(|month| month.expenses).sum(),
+    );
+    output.print(&Report { months, total }, print_text)
+}
+
+fn print_text(report: &Report) {
+    use Align::*;
+    let max = report
+        .months
+        .iter()
+        .map(|month| month.net.abs())
+        .max()
+        .unwrap_or(0);
+    let mut table = Table::new(&[Left, Right, Right, Right, Right, Left]);
+    table.push(
+        ["", "Income", "Expenses", "Net", "Saved"]
+            .map(str::to_owned)
+            .to_vec(),
+    );
+    for month in report.months.iter().chain([&report.total]) {
+        // The total would dwarf every month, so it doesn't get a bar
+        let bar = match month.month {
+            Some(_) => chart::signed_bar(month.net, max),
+            None => String::new(),
+        };
+        table.push(vec![
+            month
+                .month
+                .map_or_else(|| "Total".to_owned(), |month| month.to_string()),
+            format_cents(month.income),
+            format_cents(month.expenses),
+            format_cents(month.net),
+            month
+                .savings_rate
+                .map_or_else(String::new, |rate| format!("{rate}%")),
+            bar,
+        ]);
+    }
+    table.print();
+}
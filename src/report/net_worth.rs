@@ -0,0 +1,125 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{
+    amount::format_cents,
+    chart,
+    date::YearMonth,
+    db::{AccountType, Db},
+    output::Output,
+    table::{Align, Table},
+};
+
+#[derive(Serialize)]
+struct Accounts {
+    date: String,
+    accounts: Vec<AccountBalance>,
+    /// Cents
+    net_worth: i64,
+}
+
+#[derive(Serialize)]
+struct AccountBalance {
+    name: String,
+    #[serde(rename = "type")]
+    account_type: AccountType,
+    balance: i64,
+}
+
+#[derive(Serialize)]
+struct Month {
+    month: YearMonth,
+    /// Cents in accounts with a positive balance at the end of the month
+    assets: i64,
+    /// Cents owed on accounts with a negative balance, as a negative number
+    liabilities: i64,
+    net_worth: i64,
+    /// Change in net worth since the end of the month before
+    change: i64,
+}
+
+/// Sum the balances of every account, including credit and tracking accounts.
+/// With `monthly`, do so at the end of every month since the first transaction,
+/// up to today. Otherwise, show today's balance of each account.
+pub fn print_net_worth(db: &mut Db, today: NaiveDate, monthly: bool, output: Output) -> Result<()> {
+    if !monthly {
+        let accounts: Vec<AccountBalance> = db
+            .select_account_balances(Some(today))?
+            .into_iter()
+            .map(|(account, balance)| AccountBalance {
+                name: account.name,
+                account_type: account.account_type,
+                balance,
+            })
+            .collect();
+        let report = Accounts {
+            date: today.to_string(),
+            net_worth: accounts.iter().map(|account| account.balance).sum(),
+            accounts,
+        };
+        return output.print(&report, print_accounts);
+    }
+
+    let this_month = YearMonth::from_date(today);
+    let first_month = db
+        .select_first_date()?
+        .map_or(this_month, YearMonth::from_date);
+    let mut months = Vec::new();
+    let mut previous = 0;
+    for month in (first_month.to_int()..=this_month.to_int()).map(YearMonth::from_int) {
+        let balances: Vec<i64> = db
+            .select_account_balances(Some(month.last_day().min(today)))?
+            .into_iter()
+            .map(|(_, balance)| balance)
+            .collect();
+        let assets = balances.iter().filter(|&&balance| balance > 0).sum();
+        let liabilities = balances.iter().filter(|&&balance| balance < 0).sum();
+        let net_worth = assets + liabilities;
+        months.push(Month {
+            month,
+            assets,
+            liabilities,
+            net_worth,
+            change: net_worth - previous,
+        });
+        previous = net_worth;
+    }
+    output.print(&months, print_months)
+}
+
+fn print_accounts(report: &Accounts) {
+    println!("[ {} ]", report.date);
+    let mut table = Table::new(&[Align::Left, Align::Right]);
+    for account in &report.accounts {
+        table.push(vec![account.name.clone(), format_cents(account.balance)]);
+    }
+    table.push(vec!["Net worth".to_owned(), format_cents(report.net_worth)]);
+    table.print();
+}
+
+fn print_months(months: &Vec<Month>) {
+    use Align::*;
+    let max = months
+        .iter()
+        .map(|month| month.net_worth.abs())
+        .max()
+        .unwrap_or(0);
+    let mut table = Table::new(&[Left, Right, Right, Right, Right, Left]);
+    table.push(
+        ["", "Assets", "Liabilities", "Net worth", "Change"]
+            .map(str::to_owned)
+            .to_vec(),
+    );
+    for month in months {
+        table.push(vec![
+            month.month.to_string(),
+            format_cents(month.assets),
+            format_cents(month.liabilities),
+            format_cents(month.net_worth),
+            format_cents(month.change),
+            chart::signed_bar(month.net_worth, max),
+        ]);
+    }
+    table.print();
+}
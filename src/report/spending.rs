@@ -0,0 +1,130 @@
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use colored::Color;
+use serde::Serialize;
+
+use crate::{
+    amount::format_cents,
+    chart,
+    date::YearMonth,
+    db::{Db, Spending},
+    output::Output,
+    table::{Align, Table},
+};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    #[default]
+    Job,
+    /// The job's group; see `tbb job group`
+    Group,
+    Payee,
+    Month,
+}
+
+#[derive(Serialize)]
+struct Report {
+    from: String,
+    to: String,
+    group_by: GroupBy,
+    rows: Vec<Row>,
+    /// Cents spent in total
+    total: i64,
+}
+
+#[derive(Serialize)]
+struct Row {
+    name: String,
+    /// Cents spent, less refunds
+    amount: i64,
+    /// Share of the total, from 0 to 100
+    percent: i64,
+}
+
+/// Total up spending between two dates, inclusive.
+/// Months are listed in order, and everything else from most to least spent.
+pub fn print_spending(
+    db: &mut Db,
+    from: NaiveDate,
+    to: NaiveDate,
+    group_by: GroupBy,
+    output: Output,
+) -> Result<()> {
+    if from > to {
+        return Err(anyhow!("--from {from} is after --to {to}"));
+    }
+    let mut rows: Vec<Row> = Vec::new();
+    for spending in db.select_spending(from, to)? {
+        let name = name(&spending, group_by);
+        match rows.iter_mut().find(|row| row.name == name) {
+            Some(row) => row.amount += spending.amount,
+            None => rows.push(Row {
+                name,
+                amount: spending.amount,
+                percent: 0,
+            }),
+        }
+    }
+    if group_by != GroupBy::Month {
+        // Stable, so ties stay in the order they were first spent
+        rows.sort_by_key(|row| -row.amount);
+    }
+    let total = rows.iter().map(|row| row.amount).sum();
+    for row in &mut rows {
+        row.percent = super::percent(row.amount, total);
+    }
+    let report = Report {
+        from: from.to_string(),
+        to: to.to_string(),
+        group_by,
+        rows,
+        total,
+    };
+    output.print(&report, print_text)
+}
+
+fn name(spending: &Spending, group_by: GroupBy) -> String {
+    let name = match group_by {
+        GroupBy::Job => spending.job.clone(),
+        GroupBy::Group => spending.job.as_ref().map(|_| {
+            spending
+                .group
+                .clone()
+                .unwrap_or_else(|| "Ungrouped".to_owned())
+        }),
+        GroupBy::Payee => Some(
+            spending
+                .payee
+                .clone()
+                .unwrap_or_else(|| "No payee".to_owned()),
+        ),
+        GroupBy::Month => Some(YearMonth::from_date(spending.date).to_string()),
+    };
+    name.unwrap_or_else(|| "Uncategorized".to_owned())
+}
+
+fn print_text(report: &Report) {
+    println!("[ {} to {} ]", report.from, report.to);
+    if report.rows.is_empty() {
+        println!("No spending");
+        return;
+    }
+    let max = report.rows.iter().map(|row| row.amount).max().unwrap_or(0);
+    let mut table = Table::new(&[Align::Left, Align::Right, Align::Right, Align::Left]);
+    for row in &report.rows {
+        table.push(vec![
+            row.name.clone(),
+            format_cents(row.amount),
+            format!("{}%", row.percent),
+            chart::bar(row.amount, max, Color::Cyan),
+        ]);
+    }
+    table.push(vec![
+        "Total".to_owned(),
+        format_cents(report.total),
+        format!("{}%", super::percent(report.total, report.total)),
+    ]);
+    table.print();
+}
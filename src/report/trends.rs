@@ -0,0 +1,92 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use colored::Color;
+use serde::Serialize;
+
+use crate::{
+    amount::format_cents,
+    chart,
+    date::YearMonth,
+    db::Db,
+    output::Output,
+    table::{Align, Table},
+};
+
+#[derive(Serialize)]
+struct Report {
+    months: Vec<YearMonth>,
+    jobs: Vec<JobTrend>,
+}
+
+#[derive(Serialize)]
+struct JobTrend {
+    name: String,
+    /// Cents spent in each month, less refunds
+    spent: Vec<i64>,
+    /// Cents spent in an average month
+    average: i64,
+}
+
+/// Show how much each job spent in each of the last `months` months,
+/// up to and including the current one. Jobs that spent nothing are left out.
+pub fn print_trends(db: &mut Db, today: NaiveDate, months: u32, output: Output) -> Result<()> {
+    let this_month = YearMonth::from_date(today);
+    let first_month = YearMonth::from_int(this_month.to_int() + 1 - months as i64);
+    let mut names: Vec<Option<String>> = db
+        .select_outflow_jobs()?
+        .into_iter()
+        .map(|job| Some(job.name))
+        .collect();
+    // Uncategorized spending goes last
+    names.push(None);
+    let mut spent = vec![vec![0; months as usize]; names.len()];
+    for spending in db.select_spending(first_month.first_day(), this_month.last_day())? {
+        let job = names.iter().position(|name| *name == spending.job).unwrap();
+        let month = YearMonth::from_date(spending.date).to_int() - first_month.to_int();
+        spent[job][month as usize] += spending.amount;
+    }
+    let jobs = names
+        .into_iter()
+        .zip(spent)
+        .filter(|(_, spent)| spent.iter().any(|&amount| amount != 0))
+        .map(|(name, spent)| JobTrend {
+            name: name.unwrap_or_else(|| "Uncategorized".to_owned()),
+            average: spent.iter().sum::<i64>() / months as i64,
+            spent,
+        })
+        .collect();
+    let report = Report {
+        months: (first_month.to_int()..=this_month.to_int())
+            .map(YearMonth::from_int)
+            .collect(),
+        jobs,
+    };
+    output.print(&report, print_text)
+}
+
+fn print_text(report: &Report) {
+    let (first, last) = match (report.months.first(), report.months.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return,
+    };
+    println!("[ {first} to {last} ]");
+    if report.jobs.is_empty() {
+        println!("No spending");
+        return;
+    }
+    let mut table = Table::new(&[Align::Left, Align::Right, Align::Right, Align::Left]);
+    table.push(
+        ["", "Average", "Latest", "Trend"]
+            .map(str::to_owned)
+            .to_vec(),
+    );
+    for job in &report.jobs {
+        table.push(vec![
+            job.name.clone(),
+            format_cents(job.average),
+            format_cents(*job.spent.last().unwrap()),
+            chart::sparkline(&job.spent, Color::Cyan),
+        ]);
+    }
+    table.print();
+}
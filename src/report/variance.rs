@@ -0,0 +1,153 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::{
+    amount::format_cents,
+    date::YearMonth,
+    db::Db,
+    output::Output,
+    table::{Align, Table},
+};
+
+#[derive(Serialize)]
+struct Report {
+    month: YearMonth,
+    /// How many months, ending with `month`, are checked for overspending
+    months: u32,
+    jobs: Vec<JobVariance>,
+    total: Variance,
+}
+
+#[derive(Serialize)]
+struct JobVariance {
+    name: String,
+    #[serde(flatten)]
+    variance: Variance,
+    /// How many of the months checked the job spent more than was assigned
+    over_budget: u32,
+}
+
+#[derive(Serialize)]
+struct Variance {
+    /// Cents
+    assigned: i64,
+    /// Cents spent, less refunds
+    spent: i64,
+    /// Assigned minus spent. Negative if more was spent than assigned
+    variance: i64,
+}
+
+impl Variance {
+    fn new(assigned: i64, spent: i64) -> Variance {
+        Variance {
+            assigned,
+            spent,
+            variance: assigned - spent,
+        }
+    }
+}
+
+/// Compare what was assigned to each job in a month with what it spent,
+/// and count how many of the last `months` months it spent more than was assigned.
+/// Credit card payment jobs are left out, and so are jobs without
+/// any assignments or spending in the months checked.
+pub fn print_variance(db: &mut Db, month: YearMonth, months: u32, output: Output) -> Result<()> {
+    let mut jobs: Vec<JobVariance> = Vec::new();
+    let first_month = month.to_int() + 1 - months as i64;
+    let mut active = Vec::new();
+    for past in (first_month..=month.to_int()).map(YearMonth::from_int) {
+        let balances = db
+            .select_month_balances(past)?
+            .into_iter()
+            .filter(|(job, _)| !job.payment);
+        for (index, (job, balance)) in balances.enumerate() {
+            if jobs.len() <= index {
+                jobs.push(JobVariance {
+                    name: job.name,
+                    variance: Variance::new(0, 0),
+                    over_budget: 0,
+                });
+                active.push(false);
+            }
+            let spent = -balance.activity;
+            if spent > balance.assigned {
+                jobs[index].over_budget += 1;
+            }
+            if balance.assigned != 0 || spent != 0 {
+                active[index] = true;
+            }
+            if past == month {
+                jobs[index].variance = Variance::new(balance.assigned, spent);
+            }
+        }
+    }
+    let jobs: Vec<JobVariance> = jobs
+        .into_iter()
+        .zip(active)
+        .filter_map(|(job, active)| active.then_some(job))
+        .collect();
+    let total = Variance::new(
+        jobs.iter().map(|job| job.variance.assigned).sum(),
+        jobs.iter().map(|job| job.variance.spent).sum(),
+    );
+    let report = Report {
+        month,
+        months,
+        jobs,
+        total,
+    };
+    output.print(&report, print_text)
+}
+
+fn print_text(report: &Report) {
+    println!("[ {} ]", report.month);
+    if report.jobs.is_empty() {
+        println!("Nothing assigned or spent");
+        return;
+    }
+    use Align::*;
+    let mut table = Table::new(&[Left, Right, Right, Right, Left]);
+    table.push(vec![
+        String::new(),
+        "Assigned".to_owned(),
+        "Spent".to_owned(),
+        "Variance".to_owned(),
+        format!("Over budget in last {}", report.months),
+    ]);
+    for job in &report.jobs {
+        let over_budget = job.over_budget.to_string();
+        table.push(vec![
+            job.name.clone(),
+            format_cents(job.variance.assigned),
+            format_cents(job.variance.spent),
+            format_cents(job.variance.variance),
+            if job.over_budget == report.months {
+                over_budget.red().to_string()
+            } else {
+                over_budget
+            },
+        ]);
+    }
+    table.push(vec![
+        "Total".to_owned(),
+        format_cents(report.total.assigned),
+        format_cents(report.total.spent),
+        format_cents(report.total.variance),
+    ]);
+    table.print();
+    let consistent: Vec<&str> = report
+        .jobs
+        .iter()
+        .filter(|job| job.over_budget == report.months)
+        .map(|job| job.name.as_str())
+        .collect();
+    if !consistent.is_empty() && report.months > 1 {
+        println!();
+        println!(
+            "Over budget in each of the last {} months: {}",
+            report.months,
+            consistent.join(", ")
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{db::Db, output::Output};
+
+#[derive(Serialize)]
+struct RuleStatus {
+    id: i64,
+    payee_regex: String,
+    job: String,
+    account: Option<String>,
+}
+
+pub fn print_rules(db: &mut Db, output: Output) -> Result<()> {
+    let rules: Vec<RuleStatus> = db
+        .select_rules()?
+        .into_iter()
+        .map(|rule| RuleStatus {
+            id: rule.id,
+            payee_regex: rule.payee_regex,
+            job: rule.job,
+            account: rule.account,
+        })
+        .collect();
+    output.print(&rules, |rules| {
+        for rule in rules {
+            print_rule(rule);
+        }
+    })
+}
+
+/// Show which rule, if any, would categorize a transaction with this payee.
+pub fn print_rule_test(
+    db: &mut Db,
+    payee: &str,
+    account_name: Option<&str>,
+    output: Output,
+) -> Result<()> {
+    let rule = db
+        .select_matching_rule(payee, account_name)?
+        .map(|rule| RuleStatus {
+            id: rule.id,
+            payee_regex: rule.payee_regex,
+            job: rule.job,
+            account: rule.account,
+        });
+    output.print(&rule, |rule| match rule {
+        Some(rule) => print_rule(rule),
+        None => println!("No rule matches"),
+    })
+}
+
+fn print_rule(rule: &RuleStatus) {
+    match &rule.account {
+        Some(account) => println!(
+            "{}. /{}/ -> {} (only in {})",
+            rule.id, rule.payee_regex, rule.job, account
+        ),
+        None => println!("{}. /{}/ -> {}", rule.id, rule.payee_regex, rule.job),
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{
+    amount::format_cents,
+    db::{Db, Repeat},
+    output::Output,
+    table::{Align, Table},
+};
+
+#[derive(Serialize)]
+struct ScheduleStatus {
+    id: i64,
+    /// None once the schedule has ended
+    next: Option<String>,
+    repeat: Repeat,
+    every: u32,
+    account: String,
+    payee: Option<String>,
+    job: Option<String>,
+    amount: i64,
+    memo: String,
+}
+
+#[derive(Serialize)]
+struct Run {
+    /// How many transactions were recorded
+    recorded: usize,
+}
+
+/// Record every occurrence that is due up to today, and print how many there were.
+pub fn run_schedules(db: &mut Db, today: NaiveDate, output: Output) -> Result<()> {
+    let run = Run {
+        recorded: db.run_schedules(today)?,
+    };
+    output.print(&run, |run| {
+        println!("Recorded {} transaction(s)", run.recorded)
+    })
+}
+
+pub fn print_schedules(db: &mut Db, output: Output) -> Result<()> {
+    let schedules: Vec<ScheduleStatus> = db
+        .select_schedules()?
+        .into_iter()
+        .map(|schedule| ScheduleStatus {
+            id: schedule.id,
+            next: schedule.next().map(|date| date.to_string()),
+            repeat: schedule.repeat,
+            every: schedule.every,
+            account: schedule.account,
+            payee: schedule.payee,
+            job: schedule.job,
+            amount: schedule.amount,
+            memo: schedule.memo,
+        })
+        .collect();
+    output.print(&schedules, |schedules| {
+        if schedules.is_empty() {
+            println!("No schedules");
+            return;
+        }
+        use Align::*;
+        let mut table = Table::new(&[Right, Left, Left, Left, Left, Left, Right, Left]);
+        table.push(
+            [
+                "ID", "Next", "Repeat", "Account", "Payee", "Job", "Amount", "Memo",
+            ]
+            .map(str::to_owned)
+            .to_vec(),
+        );
+        for schedule in schedules {
+            table.push(vec![
+                schedule.id.to_string(),
+                schedule.next.clone().unwrap_or_else(|| "ended".to_owned()),
+                format_repeat(schedule.repeat, schedule.every),
+                schedule.account.clone(),
+                schedule.payee.clone().unwrap_or_default(),
+                schedule.job.clone().unwrap_or_default(),
+                format_cents(schedule.amount),
+                schedule.memo.clone(),
+            ]);
+        }
+        table.print();
+    })
+}
+
+/// "monthly" or "every 2 weeks"
+fn format_repeat(repeat: Repeat, every: u32) -> String {
+    match (repeat, every) {
+        (Repeat::Weekly, 1) => "weekly".to_owned(),
+        (Repeat::Monthly, 1) => "monthly".to_owned(),
+        (Repeat::Yearly, 1) => "yearly".to_owned(),
+        (Repeat::Weekly, every) => format!("every {every} weeks"),
+        (Repeat::Monthly, every) => format!("every {every} months"),
+        (Repeat::Yearly, every) => format!("every {every} years"),
+    }
+}
@@ -1,12 +1,264 @@
 use anyhow::Result;
 use chrono::NaiveDate;
+use colored::{Color, Colorize};
+use serde::Serialize;
 
-use crate::{date::format_month_year, db::Db};
+use crate::{
+    amount::format_cents,
+    date::{format_month_year, YearMonth},
+    db::{Db, GoalKind, OverspendingPolicy},
+    goals::{self, Progress},
+    output::Output,
+    report,
+};
 
-pub fn print_status(db: &mut Db, today: NaiveDate) -> Result<()> {
+#[derive(Serialize)]
+struct Status {
+    month: YearMonth,
+    ready_to_assign: i64,
+    /// Cents already assigned to later months, which aren't ready to assign
+    assigned_in_future: i64,
+    /// Days. None if nothing has been spent yet
+    age_of_money: Option<i64>,
+    jobs: Vec<JobStatus>,
+}
+
+#[derive(Serialize)]
+struct JobStatus {
+    name: String,
+    assigned: i64,
+    activity: i64,
+    available: i64,
+    /// What happens to a negative available amount at the end of the month
+    overspending: OverspendingPolicy,
+    goal: Option<GoalStatus>,
+    /// Only filled in when averages are asked for
+    spent: Option<SpentStatus>,
+}
+
+#[derive(Serialize)]
+struct GoalStatus {
+    kind: GoalKind,
+    amount: i64,
+    by: Option<YearMonth>,
+    #[serde(flatten)]
+    progress: Progress,
+}
+
+/// Cents spent in past months, less refunds.
+#[derive(Serialize)]
+struct SpentStatus {
+    last_month: i64,
+    average_3_months: i64,
+    average_6_months: i64,
+    average_12_months: i64,
+}
+
+/// How many past months the longest average covers.
+const AVERAGE_MONTHS: usize = 12;
+
+impl SpentStatus {
+    /// `spent` holds what was spent in each past month, starting with last month.
+    fn new(spent: &[i64; AVERAGE_MONTHS]) -> SpentStatus {
+        let average = |months: usize| spent[..months].iter().sum::<i64>() / months as i64;
+        SpentStatus {
+            last_month: spent[0],
+            average_3_months: average(3),
+            average_6_months: average(6),
+            average_12_months: average(12),
+        }
+    }
+}
+
+/// Print the current month's budget. With `averages`, also show what each job
+/// spent last month, and on average over the last 3, 6 and 12 months.
+pub fn print_status(db: &mut Db, today: NaiveDate, averages: bool, output: Output) -> Result<()> {
+    let month = YearMonth::from_date(today);
+    let goals = db.select_goals()?;
+    let balances = db.select_month_balances(month)?;
+    let mut spent = vec![[0; AVERAGE_MONTHS]; balances.len()];
+    if averages {
+        for months_ago in 1..=AVERAGE_MONTHS {
+            let past = YearMonth::from_int(month.to_int() - months_ago as i64);
+            for (spent, (_, balance)) in spent.iter_mut().zip(db.select_month_balances(past)?) {
+                spent[months_ago - 1] = -balance.activity;
+            }
+        }
+    }
+    let jobs = balances
+        .into_iter()
+        .zip(spent)
+        .map(|((job, balance), spent)| {
+            let goal = goals
+                .iter()
+                .find(|goal| goal.job == job.name)
+                .map(|goal| GoalStatus {
+                    kind: goal.kind,
+                    amount: goal.amount,
+                    by: goal.by,
+                    progress: goals::progress(goal, &balance),
+                });
+            JobStatus {
+                name: job.name,
+                assigned: balance.assigned,
+                activity: balance.activity,
+                available: balance.balance,
+                overspending: job.overspending,
+                goal,
+                spent: averages.then(|| SpentStatus::new(&spent)),
+            }
+        })
+        .collect();
+    let status = Status {
+        month,
+        ready_to_assign: db.select_ready_to_assign(month)?,
+        assigned_in_future: db.select_assigned_in_future(month)?,
+        age_of_money: report::age_of_money(db, today, report::DEFAULT_OUTFLOWS)?,
+        jobs,
+    };
+    output.print(&status, |status| print_text(status, today))
+}
+
+fn print_text(status: &Status, today: NaiveDate) {
     println!("[ {} ]", format_month_year(&today));
-    for job in db.select_outflow_jobs()? {
-        println!("{}", job.name);
+    println!("Ready to Assign: {}", format_cents(status.ready_to_assign));
+    if status.assigned_in_future != 0 {
+        println!(
+            "Assigned in future: {}",
+            format_cents(status.assigned_in_future)
+        );
+    }
+    if let Some(age) = status.age_of_money {
+        println!("Age of money: {age} days");
+    }
+    if status.jobs.is_empty() {
+        return;
+    }
+    let width = status
+        .jobs
+        .iter()
+        .map(|job| job.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    // Goal columns are only shown once some job has a goal
+    let has_goals = status.jobs.iter().any(|job| job.goal.is_some());
+    let has_averages = status.jobs.iter().any(|job| job.spent.is_some());
+    let mut header = format!(
+        "{:width$}  {:>10}  {:>10}  {:>10}",
+        "", "Assigned", "Activity", "Available"
+    );
+    if has_goals {
+        header += &format!("  {:>5}  {:>11}", "Goal", "Underfunded");
+    }
+    if has_averages {
+        header += &format!(
+            "  {:>10}  {:>10}  {:>10}  {:>10}",
+            "Last month", "3-mo avg", "6-mo avg", "12-mo avg"
+        );
+    }
+    println!("{header}");
+    for job in &status.jobs {
+        let available = format_cents(job.available);
+        let available = match available_color(job) {
+            Some(color) => available.color(color),
+            None => available.normal(),
+        };
+        let mut line = format!(
+            "{:width$}  {:>10}  {:>10}  {available:>10}",
+            job.name,
+            format_cents(job.assigned),
+            format_cents(job.activity),
+        );
+        if has_goals {
+            let (percent, underfunded) = match &job.goal {
+                Some(goal) => (
+                    format!("{}%", goal.progress.percent),
+                    format_cents(goal.progress.underfunded),
+                ),
+                None => (String::new(), String::new()),
+            };
+            line += &format!("  {percent:>5}  {underfunded:>11}");
+        }
+        if let Some(spent) = &job.spent {
+            line += &format!(
+                "  {:>10}  {:>10}  {:>10}  {:>10}",
+                format_cents(spent.last_month),
+                format_cents(spent.average_3_months),
+                format_cents(spent.average_6_months),
+                format_cents(spent.average_12_months),
+            );
+        }
+        println!("{}", line.trim_end());
+    }
+    let overspent: Vec<&JobStatus> = status.jobs.iter().filter(|job| job.available < 0).collect();
+    if !overspent.is_empty() {
+        println!();
+        for job in overspent {
+            let consequence = match job.overspending {
+                OverspendingPolicy::Carry => "carries into next month",
+                OverspendingPolicy::Deduct => "comes out of next month's Ready to Assign",
+            };
+            println!(
+                "{} {} by {}, which {consequence}",
+                "Overspent:".red().bold(),
+                job.name,
+                format_cents(-job.available)
+            );
+        }
+    }
+}
+
+/// Red if the job is overspent, yellow if its goal is underfunded,
+/// and green if it has money available and its goal, if any, is funded.
+fn available_color(job: &JobStatus) -> Option<Color> {
+    let underfunded = job
+        .goal
+        .as_ref()
+        .is_some_and(|goal| goal.progress.underfunded > 0);
+    if job.available < 0 {
+        Some(Color::Red)
+    } else if underfunded {
+        Some(Color::Yellow)
+    } else if job.available > 0 {
+        Some(Color::Green)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(available: i64, underfunded: Option<i64>) -> JobStatus {
+        JobStatus {
+            name: "groceries".to_owned(),
+            assigned: 0,
+            activity: 0,
+            available,
+            overspending: OverspendingPolicy::default(),
+            goal: underfunded.map(|underfunded| GoalStatus {
+                kind: GoalKind::Target,
+                amount: 10_000,
+                by: None,
+                progress: Progress {
+                    underfunded,
+                    percent: 0,
+                },
+            }),
+            spent: None,
+        }
+    }
+
+    #[test]
+    fn available_is_colored_by_how_the_job_is_doing() {
+        assert_eq!(available_color(&job(-100, None)), Some(Color::Red));
+        assert_eq!(available_color(&job(-100, Some(500))), Some(Color::Red));
+        assert_eq!(available_color(&job(100, Some(500))), Some(Color::Yellow));
+        assert_eq!(available_color(&job(0, Some(500))), Some(Color::Yellow));
+        assert_eq!(available_color(&job(100, Some(0))), Some(Color::Green));
+        assert_eq!(available_color(&job(100, None)), Some(Color::Green));
+        assert_eq!(available_color(&job(0, Some(0))), None);
+        assert_eq!(available_color(&job(0, None)), None);
     }
-    Ok(())
 }
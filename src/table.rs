@@ -0,0 +1,59 @@
+//! Prints rows of text as aligned columns.
+
+#[derive(Clone, Copy)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+pub struct Table {
+    aligns: Vec<Align>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Create a table with one column per alignment.
+    pub fn new(aligns: &[Align]) -> Table {
+        Table {
+            aligns: aligns.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Rows with fewer cells than the table has columns are padded with empty cells.
+    pub fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    /// Print every row, with columns separated by two spaces.
+    /// Trailing whitespace is trimmed from each line.
+    pub fn print(&self) {
+        let widths: Vec<usize> = (0..self.aligns.len())
+            .map(|column| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(column))
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        for row in &self.rows {
+            let line = self
+                .aligns
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(column, (align, &width))| {
+                    let cell = row.get(column).map(String::as_str).unwrap_or("");
+                    match align {
+                        Align::Left => format!("{cell:<width$}"),
+                        Align::Right => format!("{cell:>width$}"),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("  ");
+            println!("{}", line.trim_end());
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{
+    amount::format_cents,
+    db::Db,
+    output::Output,
+    table::{Align, Table},
+};
+
+#[derive(Serialize)]
+struct TemplateStatus {
+    name: String,
+    amounts: Vec<AmountStatus>,
+}
+
+#[derive(Serialize)]
+struct AmountStatus {
+    job: String,
+    amount: i64,
+}
+
+pub fn print_templates(db: &mut Db, output: Output) -> Result<()> {
+    let templates: Vec<TemplateStatus> = db
+        .select_templates()?
+        .into_iter()
+        .map(|template| TemplateStatus {
+            name: template.name,
+            amounts: template
+                .amounts
+                .into_iter()
+                .map(|(job, amount)| AmountStatus { job, amount })
+                .collect(),
+        })
+        .collect();
+    output.print(&templates, |templates| {
+        let mut table = Table::new(&[Align::Left, Align::Right]);
+        for template in templates {
+            table.push(vec![template.name.clone()]);
+            for amount in &template.amounts {
+                table.push(vec![
+                    format!("  {}", amount.job),
+                    format_cents(amount.amount),
+                ]);
+            }
+        }
+        table.print();
+    })
+}
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{
+    amount::format_cents,
+    db::{ClearedStatus, Db, TransactionFilter},
+    output::Output,
+    table::{Align, Table},
+};
+
+#[derive(Serialize)]
+struct TransactionStatus {
+    id: i64,
+    date: String,
+    account: String,
+    payee: Option<String>,
+    amount: i64,
+    memo: String,
+    status: ClearedStatus,
+    /// The other account, if this is one side of a transfer
+    transfer: Option<String>,
+    /// Empty if the transaction is uncategorized
+    splits: Vec<SplitStatus>,
+}
+
+#[derive(Serialize)]
+struct SplitStatus {
+    job: String,
+    amount: i64,
+}
+
+pub fn print_transactions(db: &mut Db, filter: &TransactionFilter, output: Output) -> Result<()> {
+    let transactions: Vec<TransactionStatus> = db
+        .select_transactions(filter)?
+        .into_iter()
+        .map(|tx| TransactionStatus {
+            id: tx.id,
+            date: tx.date.to_string(),
+            account: tx.account,
+            payee: tx.payee,
+            amount: tx.amount,
+            memo: tx.memo,
+            status: tx.status,
+            transfer: tx.transfer,
+            splits: tx
+                .splits
+                .into_iter()
+                .map(|split| SplitStatus {
+                    job: split.job,
+                    amount: split.amount,
+                })
+                .collect(),
+        })
+        .collect();
+    output.print(&transactions, |transactions| {
+        if transactions.is_empty() {
+            println!("No transactions");
+            return;
+        }
+        use Align::*;
+        let mut table = Table::new(&[Right, Left, Left, Left, Left, Right, Left, Left]);
+        table.push(
+            [
+                "ID", "Date", "Account", "Payee", "Job", "Amount", "C", "Memo",
+            ]
+            .map(str::to_owned)
+            .to_vec(),
+        );
+        for tx in transactions {
+            let job = match tx.splits.as_slice() {
+                [] => String::new(),
+                [split] => split.job.clone(),
+                _ => "(split)".to_owned(),
+            };
+            table.push(vec![
+                tx.id.to_string(),
+                tx.date.clone(),
+                tx.account.clone(),
+                match &tx.transfer {
+                    Some(account) => format!("Transfer: {account}"),
+                    None => tx.payee.clone().unwrap_or_default(),
+                },
+                job,
+                format_cents(tx.amount),
+                match tx.status {
+                    ClearedStatus::Uncleared => "",
+                    ClearedStatus::Cleared => "c",
+                    ClearedStatus::Reconciled => "R",
+                }
+                .to_owned(),
+                tx.memo.clone(),
+            ]);
+            // Show each split of a split transaction on its own line
+            if tx.splits.len() > 1 {
+                for split in &tx.splits {
+                    table.push(vec![
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                        format!("  {}", split.job),
+                        format_cents(split.amount),
+                    ]);
+                }
+            }
+        }
+        table.print();
+    })
+}
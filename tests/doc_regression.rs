@@ -12,7 +12,7 @@ fn test_doc_regression() -> anyhow::Result<()> {
         let path = path.to_str().expect("doc path is not a string");
         with_doc(path, |contents, date, db_path| {
             for_each_code_block(contents, |code| {
-                let new_code = run_commands(code, Mode::Run, &db_path, &date);
+                let new_code = run_commands(code, Mode::Run, db_path, date);
                 if new_code.is_err() {
                     eprintln!("Command failed to run in file: {path}");
                     eprintln!("Code:\n{code}");